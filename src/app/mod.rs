@@ -1,2 +1,4 @@
+pub mod snapshot;
 pub mod state;
+pub mod theme;
 pub mod ui;
@@ -0,0 +1,176 @@
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::app::state::App;
+use crate::models::crypto::Quote;
+use crate::models::portfolio::{PortfolioTotals, Position};
+use crate::utils::formatters::{format_amount, format_market_cap, format_percent, format_price, format_volume};
+
+/// Renders the watchlist and portfolio totals as plain aligned text for `--once`.
+/// Rows come from the same sorting and portfolio math as the interactive view
+pub fn render_text(app: &App) -> String {
+    let currency = app.config.convert_currency.as_str();
+    let change = |value: Option<f64>| format_percent(value, app.config.percent_precision);
+
+    let mut out = format!(
+        "{:<8} {:>14} {:>9} {:>9} {:>9} {:>12} {:>12}\n",
+        "Symbol", "Price", "1h", "24h", "7d", "Volume 24h", "Market Cap"
+    );
+    for crypto in app.watchlist_rows() {
+        match app.quote(crypto) {
+            Some(quote) => out.push_str(&format!(
+                "{:<8} {:>14} {:>9} {:>9} {:>9} {:>12} {:>12}\n",
+                crypto.symbol,
+                format_price(quote.price, currency),
+                change(quote.percent_change_1h),
+                change(quote.percent_change_24h),
+                change(quote.percent_change_7d),
+                format_volume(quote.volume_24h, currency),
+                format_market_cap(quote.market_cap, currency),
+            )),
+            None => out.push_str(&format!(
+                "{:<8} {:>14} {:>9} {:>9} {:>9} {:>12} {:>12}\n",
+                crypto.symbol, "N/A", "N/A", "N/A", "N/A", "N/A", "N/A"
+            )),
+        }
+    }
+
+    let positions = app.real_positions();
+    if !positions.is_empty() {
+        let totals = PortfolioTotals::from_positions(&positions);
+        out.push_str(&format!(
+            "\nPortfolio: {} positions | Value: {} | Cost: {} | P/L: {} ({})\n",
+            positions.len(),
+            format_amount(totals.value, currency),
+            format_amount(totals.cost, currency),
+            format_amount(totals.profit_loss, currency),
+            format_percent(Some(totals.profit_loss_pct), 2)
+        ));
+    }
+
+    out
+}
+
+#[derive(Serialize)]
+struct JsonSnapshot<'a> {
+    currency: &'a str,
+    /// RFC 3339 time of the fetch the figures come from
+    fetched_at: Option<String>,
+    watchlist: Vec<WatchlistEntry<'a>>,
+    portfolio: PortfolioSummary,
+}
+
+#[derive(Serialize)]
+struct WatchlistEntry<'a> {
+    symbol: &'a str,
+    name: &'a str,
+    slug: Option<&'a str>,
+    /// Missing when CMC has no quote in the configured currency
+    quote: Option<&'a Quote>,
+}
+
+#[derive(Serialize)]
+struct PortfolioSummary {
+    totals: PortfolioTotals,
+    positions: Vec<PositionEntry>,
+}
+
+/// Per-token portfolio figures; market-dependent ones are null for unpriced holdings
+#[derive(Serialize)]
+struct PositionEntry {
+    symbol: String,
+    holdings: f64,
+    avg_buy_price: f64,
+    cost_basis: f64,
+    price: Option<f64>,
+    current_value: Option<f64>,
+    profit_loss: Option<f64>,
+    profit_loss_pct: Option<f64>,
+    realized_profit_loss: f64,
+    annual_yield: Option<f64>,
+}
+
+impl From<&Position<'_>> for PositionEntry {
+    fn from(position: &Position) -> Self {
+        let priced = |value: f64| position.quote.map(|_| value);
+        PositionEntry {
+            symbol: position.crypto.symbol.clone(),
+            holdings: position.holdings,
+            avg_buy_price: position.avg_buy_price,
+            cost_basis: position.cost_basis,
+            price: position.quote.map(|quote| quote.price),
+            current_value: priced(position.current_value),
+            profit_loss: priced(position.profit_loss),
+            profit_loss_pct: priced(position.profit_loss_pct),
+            realized_profit_loss: position.realized_profit_loss,
+            annual_yield: position.annual_yield,
+        }
+    }
+}
+
+/// Renders the watchlist and portfolio as pretty-printed JSON for `--json`
+pub fn render_json(app: &App) -> Result<String> {
+    let snapshot = JsonSnapshot {
+        currency: &app.config.convert_currency,
+        fetched_at: fetched_at(app),
+        watchlist: watchlist_entries(app),
+        portfolio: portfolio_summary(app),
+    };
+    Ok(serde_json::to_string_pretty(&snapshot)?)
+}
+
+#[derive(Serialize)]
+struct PricesSnapshot<'a> {
+    currency: &'a str,
+    fetched_at: Option<String>,
+    watchlist: Vec<WatchlistEntry<'a>>,
+}
+
+#[derive(Serialize)]
+struct PortfolioSnapshot<'a> {
+    currency: &'a str,
+    fetched_at: Option<String>,
+    #[serde(flatten)]
+    portfolio: PortfolioSummary,
+}
+
+/// Renders just the watchlist as JSON, for the server's `/prices`
+pub fn render_prices_json(app: &App) -> Result<String> {
+    Ok(serde_json::to_string(&PricesSnapshot {
+        currency: &app.config.convert_currency,
+        fetched_at: fetched_at(app),
+        watchlist: watchlist_entries(app),
+    })?)
+}
+
+/// Renders just the portfolio totals and positions as JSON, for the server's `/portfolio`
+pub fn render_portfolio_json(app: &App) -> Result<String> {
+    Ok(serde_json::to_string(&PortfolioSnapshot {
+        currency: &app.config.convert_currency,
+        fetched_at: fetched_at(app),
+        portfolio: portfolio_summary(app),
+    })?)
+}
+
+fn fetched_at(app: &App) -> Option<String> {
+    app.last_update.map(|time| time.to_rfc3339())
+}
+
+fn watchlist_entries(app: &App) -> Vec<WatchlistEntry<'_>> {
+    app.watchlist_rows().into_iter()
+        .map(|crypto| WatchlistEntry {
+            symbol: &crypto.symbol,
+            name: &crypto.name,
+            slug: crypto.slug.as_deref(),
+            quote: app.quote(crypto),
+        })
+        .collect()
+}
+
+fn portfolio_summary(app: &App) -> PortfolioSummary {
+    let positions = app.real_positions();
+    PortfolioSummary {
+        totals: PortfolioTotals::from_positions(&positions),
+        positions: positions.iter().map(PositionEntry::from).collect(),
+    }
+}
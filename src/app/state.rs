@@ -1,12 +1,52 @@
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use tui::widgets::TableState;
-use chrono::{DateTime, Local};
-use anyhow::Result;
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use anyhow::{Context, Result};
 
-use crate::models::config::{Config, TokenConfig};
-use crate::models::crypto::CryptoData;
+use crate::app::theme::Theme;
+use crate::models::config::{Alert, AlertDirection, Config, FearGreedAlert, FearGreedZone, PaperAccount, TokenConfig, Transaction, TransactionKind, WATCHLIST_COLUMN_NAMES};
+use crate::models::crypto::{CryptoData, OhlcvQuote, Quote};
 use crate::models::fear_greed::FearGreedData;
-use crate::services::api;
+use crate::models::market::GlobalMetrics;
+use crate::models::portfolio::{PortfolioTotals, Position};
+use crate::services::{api, cache, clipboard};
+use crate::services::cache::{LastViewed, ValuePoint};
+use crate::services::api::{Credits, PriceFetch};
+use crate::services::provider::{self, PriceProvider};
+use crate::services::logger::{log_error, log_info};
+use crate::services::notifier;
+use crate::utils::formatters::{self, format_price, NumberLocale};
+use crate::utils::indicators;
+
+/// Number of daily candles shown in the detail view's price chart
+const DETAIL_HISTORY_DAYS: u32 = 7;
+
+/// Daily candles fetched for the RSI column; a couple of periods' worth lets the
+/// smoothing settle
+const RSI_HISTORY_DAYS: u32 = indicators::RSI_PERIOD as u32 * 2 + 1;
+
+/// Prices count as stale once they are this many refresh intervals old
+const STALE_AFTER_INTERVALS: u64 = 3;
+
+/// Maximum number of executed commands kept for up/down recall
+const MAX_COMMAND_HISTORY: usize = 100;
+
+/// How many times above or below the market price an entered avg buy price can be
+/// before it's treated as a likely typo and needs confirming
+const AVG_PRICE_SANITY_RATIO: f64 = 100.0;
+
+/// Outcome of `import`: the (name, avg_buy_price) of each imported row, and why any
+/// other rows were skipped
+struct PortfolioImport {
+    imported: Vec<(String, f64)>,
+    skipped: Vec<String>,
+}
+
+/// How many times apart two positive prices are, whichever is larger
+fn price_ratio(a: f64, b: f64) -> f64 {
+    (a / b).max(b / a)
+}
 
 #[derive(Debug)]
 pub enum Command {
@@ -22,6 +62,54 @@ pub enum Command {
         watchlist: bool,
         portfolio: bool,
     },
+    Set {
+        name: String,
+        owned: Option<f64>,
+        avg_buy_price: Option<f64>,
+    },
+    Trade {
+        name: String,
+        kind: TransactionKind,
+        quantity: f64,
+        price: f64,
+    },
+    Alert {
+        name: String,
+        direction: AlertDirection,
+        price: f64,
+    },
+    Top {
+        n: u32,
+    },
+    Pin {
+        name: String,
+        pinned: bool,
+    },
+    /// Replaces a token's tags; an empty list clears them
+    Tag {
+        name: String,
+        tags: Vec<String>,
+    },
+    Note {
+        name: String,
+        note: Option<String>,
+    },
+    Export {
+        path: PathBuf,
+    },
+    Import {
+        path: PathBuf,
+    },
+    FearGreedLimit {
+        days: u32,
+    },
+    /// Sets the fear & greed alert thresholds; `None` turns the alert off
+    FearGreedAlert(Option<(u64, u64)>),
+    /// Toggles paper trading, or with `reset` empties the paper account
+    Paper { reset: bool },
+    Reload,
+    /// Previews an add/rm/set without touching the config file
+    DryRun(Box<Command>),
     Invalid(String),
 }
 
@@ -29,6 +117,16 @@ pub enum Command {
 pub enum InputMode {
     Normal,
     Editing,
+    Detail,
+    Confirm,
+    Filter,
+}
+
+/// A command that only runs once the user answers `y` to its prompt
+#[derive(Debug)]
+pub struct PendingConfirmation {
+    pub prompt: String,
+    pub command: Command,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -47,70 +145,850 @@ pub enum SortColumn {
     Holdings,
     AvgBuy,
     CurrentValue,
+    Allocation,
     CostBasis,
     ProfitLoss,
     ProfitLossPercent,
+    BreakEven,
+    ToBreakEven,
+    AnnualYield,
+}
+
+impl SortColumn {
+    /// The watchlist column a `columns` config entry names
+    pub fn from_column_name(name: &str) -> Option<SortColumn> {
+        match name {
+            "symbol" => Some(SortColumn::Symbol),
+            "price" => Some(SortColumn::Price),
+            "change_1h" => Some(SortColumn::Change1h),
+            "change_24h" => Some(SortColumn::Change24h),
+            "change_7d" => Some(SortColumn::Change7d),
+            "change_30d" => Some(SortColumn::Change30d),
+            "change_90d" => Some(SortColumn::Change90d),
+            "volume_24h" => Some(SortColumn::Volume24h),
+            "volume_change" => Some(SortColumn::VolumeChange),
+            "market_cap" => Some(SortColumn::MarketCap),
+            _ => None,
+        }
+    }
+}
+
+/// Period the portfolio summary's headline change is measured over
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChangeWindow {
+    Hour,
+    Day,
+    Week,
+    Month,
+}
+
+impl ChangeWindow {
+    /// The next window in the 1h → 24h → 7d → 30d cycle
+    pub fn next(self) -> Self {
+        match self {
+            ChangeWindow::Hour => ChangeWindow::Day,
+            ChangeWindow::Day => ChangeWindow::Week,
+            ChangeWindow::Week => ChangeWindow::Month,
+            ChangeWindow::Month => ChangeWindow::Hour,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ChangeWindow::Hour => "1h",
+            ChangeWindow::Day => "24h",
+            ChangeWindow::Week => "7d",
+            ChangeWindow::Month => "30d",
+        }
+    }
+
+    /// The quote's percent change over this window
+    pub fn percent_change(self, quote: &Quote) -> Option<f64> {
+        match self {
+            ChangeWindow::Hour => quote.percent_change_1h,
+            ChangeWindow::Day => quote.percent_change_24h,
+            ChangeWindow::Week => quote.percent_change_7d,
+            ChangeWindow::Month => quote.percent_change_30d,
+        }
+    }
+}
+
+/// Order of the portfolio summary's allocation list, independent of the table sort
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AllocationSort {
+    Percentage,
+    Value,
+    Symbol,
+}
+
+impl AllocationSort {
+    /// The next key in the percentage → value → symbol cycle
+    pub fn next(self) -> Self {
+        match self {
+            AllocationSort::Percentage => AllocationSort::Value,
+            AllocationSort::Value => AllocationSort::Symbol,
+            AllocationSort::Symbol => AllocationSort::Percentage,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AllocationSort::Percentage => "by %",
+            AllocationSort::Value => "by value",
+            AllocationSort::Symbol => "by symbol",
+        }
+    }
 }
 
 pub struct App {
     pub config: Config,
+    provider: Box<dyn PriceProvider>,
+    pub config_path: PathBuf,
+    pub theme: Theme,
     pub table_state: TableState,
+    /// Scroll state of the grouped watchlist, whose section header rows shift the
+    /// display indices away from `table_state`'s
+    pub grouped_table_state: TableState,
+    /// Rows visible in the active table as of the last draw, used for paging
+    pub page_size: usize,
+    /// Symbol of the highlighted coin, so the same coin stays selected when
+    /// refreshes or sorting reorder the rows
+    selected_coin: Option<String>,
     pub crypto_data: HashMap<String, CryptoData>,
     pub last_update: Option<DateTime<Local>>,
+    pub cached_at: Option<DateTime<Local>>,
+    pub is_loading: bool,
     pub last_error: Option<String>,
+    pub status_message: Option<String>,
     pub fear_greed_data: Vec<FearGreedData>,
+    /// Why the last fear & greed fetch failed, cleared by the next success
+    pub fear_greed_error: Option<String>,
+    pub global_metrics: Option<GlobalMetrics>,
+    pub price_history: HashMap<String, Vec<OhlcvQuote>>,
+    /// RSI of each watched symbol's daily closes; `None` while it's being fetched or
+    /// when there's too little history
+    pub rsi: HashMap<String, Option<f64>>,
+    /// UTC day the RSI values were requested on; a new daily candle makes them stale
+    rsi_day: Option<NaiveDate>,
+    /// Total portfolio value after each successful fetch, oldest first
+    pub value_history: Vec<ValuePoint>,
+    /// Prices from when the app was last closed, for the change since the last visit
+    pub last_viewed: Option<LastViewed>,
+    /// API credits left, as reported by CoinMarketCap's usage headers
+    pub credits: Option<Credits>,
+    /// How many times `reload` has applied the config file; the background polls
+    /// restart, including ones parked on a rejected key, when this changes
+    pub reloads: u64,
+    /// 24h change of each watched symbol at the previous refresh, for the change bell
+    last_changes: HashMap<String, f64>,
+    pub detail_symbol: Option<String>,
     pub tab_index: usize,
     pub sort_column: SortColumn,
-    pub sort_ascending: bool,
+    pub watchlist_sort_ascending: bool,
     pub portfolio_sort_column: SortColumn,
+    pub portfolio_sort_ascending: bool,
     pub input_mode: InputMode,
+    /// Whether the keybinding reference overlay is open
+    pub show_help: bool,
+    /// Layout picked with `w`; `None` follows the terminal width
+    pub compact_layout: Option<bool>,
+    /// Whether the last draw used the compact watchlist layout
+    pub compact_active: bool,
+    /// Window of the portfolio summary's change figure, cycled with `c`
+    pub summary_window: ChangeWindow,
+    /// Automatic price updates are held back while set, toggled with `p`; `r` still refreshes
+    pub paused: bool,
+    /// Whether `buy`/`sell` and the portfolio views use the paper account, toggled with `paper`
+    pub paper_mode: bool,
+    /// Order of the summary's allocation list, cycled with `a`
+    pub allocation_sort: AllocationSort,
     pub input: String,
+    pub filter: Option<String>,
+    pub pending_confirmation: Option<PendingConfirmation>,
+    pub command_history: Vec<String>,
+    pub history_index: Option<usize>,
 }
 
 impl App {
-    pub fn new(config: Config) -> App {
+    pub fn new(config: Config, config_path: PathBuf) -> App {
         App {
+            theme: Theme::from_name(config.theme.as_deref()),
+            provider: provider::from_config(&config),
             config,
+            config_path,
             table_state: TableState::default(),
+            grouped_table_state: TableState::default(),
+            page_size: 1,
+            selected_coin: None,
             crypto_data: HashMap::new(),
             last_update: None,
+            cached_at: None,
+            is_loading: false,
             last_error: None,
+            status_message: None,
             fear_greed_data: Vec::new(),
+            fear_greed_error: None,
+            global_metrics: None,
+            price_history: HashMap::new(),
+            rsi: HashMap::new(),
+            rsi_day: None,
+            value_history: Vec::new(),
+            last_viewed: None,
+            credits: None,
+            reloads: 0,
+            last_changes: HashMap::new(),
+            detail_symbol: None,
             tab_index: 0,
             sort_column: SortColumn::MarketCap,
-            sort_ascending: false,
+            watchlist_sort_ascending: false,
             portfolio_sort_column: SortColumn::CurrentValue,
+            portfolio_sort_ascending: false,
             input_mode: InputMode::Normal,
+            show_help: false,
+            compact_layout: None,
+            compact_active: false,
+            summary_window: ChangeWindow::Day,
+            paused: false,
+            paper_mode: false,
+            allocation_sort: AllocationSort::Percentage,
             input: String::new(),
+            filter: None,
+            pending_confirmation: None,
+            command_history: Vec::new(),
+            history_index: None,
         }
     }
 
     pub fn enter_edit_mode(&mut self) {
         self.input_mode = InputMode::Editing;
         self.input.clear();
+        self.status_message = None;
+    }
+
+    /// Dismisses the error and status messages
+    pub fn clear_messages(&mut self) {
+        self.last_error = None;
+        self.status_message = None;
     }
 
     pub fn exit_edit_mode(&mut self) {
         self.input_mode = InputMode::Normal;
         self.input.clear();
+        self.history_index = None;
     }
 
-    pub async fn fetch_prices(&self) -> Result<HashMap<String, CryptoData>> {
-        let token_names: Vec<String> = self.config.tokens
+    /// Starts typing a watchlist filter; rows narrow down as the user types
+    pub fn enter_filter_mode(&mut self) {
+        self.input_mode = InputMode::Filter;
+        self.filter = Some(String::new());
+        self.restore_selection();
+    }
+
+    /// Keeps the typed filter (an empty one clears it) and returns to normal mode
+    pub fn apply_filter(&mut self) {
+        if self.filter.as_deref().is_some_and(str::is_empty) {
+            self.filter = None;
+        }
+        self.input_mode = InputMode::Normal;
+    }
+
+    pub fn clear_filter(&mut self) {
+        self.filter = None;
+        self.input_mode = InputMode::Normal;
+        self.restore_selection();
+    }
+
+    /// Recalls the previous (older) command from history into the input field
+    pub fn history_previous(&mut self) {
+        if self.command_history.is_empty() {
+            return;
+        }
+        let i = match self.history_index {
+            Some(i) => i.saturating_sub(1),
+            None => self.command_history.len() - 1,
+        };
+        self.history_index = Some(i);
+        self.input = self.command_history[i].clone();
+    }
+
+    /// Recalls the next (newer) command, clearing the input past the newest one
+    pub fn history_next(&mut self) {
+        match self.history_index {
+            Some(i) if i + 1 < self.command_history.len() => {
+                self.history_index = Some(i + 1);
+                self.input = self.command_history[i + 1].clone();
+            }
+            Some(_) => {
+                self.history_index = None;
+                self.input.clear();
+            }
+            None => {}
+        }
+    }
+
+    fn record_command(&mut self) {
+        let command = self.input.trim().to_string();
+        self.history_index = None;
+        if command.is_empty() || self.command_history.last() == Some(&command) {
+            return;
+        }
+        self.command_history.push(command);
+        if self.command_history.len() > MAX_COMMAND_HISTORY {
+            self.command_history.remove(0);
+        }
+    }
+
+    /// Opens the detail popup for the selected watchlist row, fetching its
+    /// price history the first time the coin is viewed
+    pub async fn open_detail(&mut self) {
+        let selected = self.selected_symbol()
+            .and_then(|symbol| self.crypto_data.values().find(|crypto| crypto.symbol == symbol))
+            .cloned();
+        let coin = match selected {
+            Some(coin) => coin,
+            None => return,
+        };
+        let symbol = coin.symbol.clone();
+
+        if !self.price_history.contains_key(&symbol) {
+            match self.provider.fetch_ohlcv(&coin, DETAIL_HISTORY_DAYS, &self.config.convert_currency).await {
+                Ok(history) => {
+                    self.price_history.insert(symbol.clone(), history);
+                }
+                Err(e) => self.last_error = Some(format!("Price history error: {}", e)),
+            }
+        }
+
+        self.detail_symbol = Some(symbol);
+        self.input_mode = InputMode::Detail;
+    }
+
+    pub fn close_detail(&mut self) {
+        self.detail_symbol = None;
+        self.input_mode = InputMode::Normal;
+    }
+
+    /// Watched coins whose RSI hasn't been requested yet today. They're marked as
+    /// requested, so each coin is fetched at most once a day
+    pub fn take_rsi_requests(&mut self) -> Vec<CryptoData> {
+        let today = Utc::now().date_naive();
+        if self.rsi_day != Some(today) {
+            self.rsi.clear();
+            self.rsi_day = Some(today);
+        }
+
+        let requests: Vec<CryptoData> = self.crypto_data.values()
+            .filter(|crypto| !self.rsi.contains_key(&crypto.symbol))
+            .filter(|crypto| self.token_for(crypto).is_some_and(|token| token.is_in_watchlist()))
+            .cloned()
+            .collect();
+        for crypto in &requests {
+            self.rsi.insert(crypto.symbol.clone(), None);
+        }
+        requests
+    }
+
+    /// RSI of a coin's recent daily closes, or `None` if the provider returned too few candles
+    pub async fn fetch_rsi(&self, coin: &CryptoData) -> Result<Option<f64>> {
+        let currency = &self.config.convert_currency;
+        let history = self.provider.fetch_ohlcv(coin, RSI_HISTORY_DAYS, currency).await?;
+        let closes: Vec<f64> = history.iter()
+            .filter_map(|candle| candle.quote.get(currency).map(|values| values.close))
+            .collect();
+        Ok(indicators::rsi(&closes, indicators::RSI_PERIOD))
+    }
+
+    pub async fn fetch_prices(&self) -> Result<PriceFetch> {
+        let mut token_names: Vec<String> = self.config.tokens
             .iter()
             .map(|token| token.name.clone())
             .collect();
-        api::fetch_prices(&self.config.api_key, &token_names).await
+        // Paper positions need prices too, even for coins that aren't otherwise tracked
+        for position in self.config.paper.iter().flat_map(|paper| &paper.positions) {
+            if !token_names.iter().any(|name| name.eq_ignore_ascii_case(&position.name)) {
+                token_names.push(position.name.clone());
+            }
+        }
+        self.provider.fetch_prices(&token_names, &self.config.convert_currency).await
+    }
+
+    /// Shows prices from the on-disk cache until the first live fetch lands, along
+    /// with the stored portfolio value history
+    pub fn load_cached_prices(&mut self) {
+        if let Some((data, fetched_at)) = cache::load_prices() {
+            self.crypto_data = data;
+            self.cached_at = Some(fetched_at);
+        }
+        self.value_history = cache::load_value_history();
+        // Prices in another currency can't be compared
+        self.last_viewed = cache::load_last_viewed()
+            .filter(|snapshot| snapshot.currency == self.config.convert_currency);
+    }
+
+    /// Stores the current prices so the next session can show what moved in between.
+    /// Nothing is written before the first prices arrive, keeping the older snapshot
+    pub fn save_last_viewed(&self) {
+        let prices: HashMap<String, f64> = self.crypto_data.values()
+            .filter_map(|crypto| Some((crypto.symbol.clone(), self.quote(crypto)?.price)))
+            .collect();
+        if prices.is_empty() {
+            return;
+        }
+        let snapshot = LastViewed {
+            viewed_at: Local::now().timestamp(),
+            currency: self.config.convert_currency.clone(),
+            prices,
+        };
+        if let Err(e) = cache::save_last_viewed(&snapshot) {
+            log_error("Cache Write Error", &e.to_string()).unwrap_or(());
+        }
+    }
+
+    /// Percent change of a coin's price since the previous session ended, if it was
+    /// priced then
+    pub fn change_since_last_viewed(&self, crypto: &CryptoData) -> Option<f64> {
+        let then = *self.last_viewed.as_ref()?.prices.get(&crypto.symbol)?;
+        let now = self.quote(crypto)?.price;
+        (then > 0.0).then(|| (now - then) / then * 100.0)
+    }
+
+    /// Applies freshly fetched prices and writes them to the on-disk cache.
+    /// Batches that failed and tokens the API didn't recognize are reported through
+    /// `last_error`, which a complete fetch clears
+    pub fn set_prices(&mut self, fetch: PriceFetch) {
+        let now = Local::now();
+        if let Err(e) = cache::save_prices(&fetch.data, now) {
+            log_error("Cache Write Error", &e.to_string()).unwrap_or(());
+        }
+        for crypto in fetch.data.values() {
+            if !crypto.quote.contains_key(&self.config.convert_currency) {
+                log_error("Missing Quote", &format!(
+                    "No {} quote for {} ({})", self.config.convert_currency, crypto.symbol, crypto.name))
+                    .unwrap_or(());
+            }
+        }
+        if !fetch.missing.is_empty() {
+            log_error("Unknown Coins", &fetch.missing.join(", ")).unwrap_or(());
+        }
+        let mut problems = Vec::new();
+        if !fetch.warnings.is_empty() {
+            problems.push(format!("Partial update, some prices failed: {}", fetch.warnings.join("; ")));
+        }
+        if !fetch.missing.is_empty() {
+            problems.push(format!("Unknown coins: {} — check spelling", fetch.missing.join(", ")));
+        }
+        self.last_error = (!problems.is_empty()).then(|| problems.join(" | "));
+        self.crypto_data = fetch.data;
+        self.last_update = Some(now);
+        self.cached_at = None;
+        self.record_portfolio_value(now);
+        self.restore_selection();
+    }
+
+    /// Fires a desktop notification for each alert whose threshold the latest prices
+    /// have crossed, and re-arms alerts whose price has moved back
+    pub fn check_alerts(&mut self) {
+        let mut changed = false;
+        let mut fired = Vec::new();
+
+        for alert in &mut self.config.alerts {
+            let crypto = self.config.tokens.iter()
+                .find(|t| t.name.to_lowercase() == alert.token.to_lowercase())
+                .and_then(|token| self.crypto_data.values().find(|c| token.matches_crypto(c)));
+            let price = match crypto.and_then(|c| c.quote.get(&self.config.convert_currency)) {
+                Some(quote) => quote.price,
+                None => continue,
+            };
+
+            let crossed = alert.is_crossed(price);
+            if crossed && !alert.triggered {
+                let symbol = crypto.map(|c| c.symbol.as_str()).unwrap_or(&alert.token);
+                fired.push(format!(
+                    "{} is {} {} (now {})",
+                    symbol,
+                    if alert.direction == AlertDirection::Above { "above" } else { "below" },
+                    format_price(alert.price, &self.config.convert_currency),
+                    format_price(price, &self.config.convert_currency),
+                ));
+            }
+            if crossed != alert.triggered {
+                alert.triggered = crossed;
+                changed = true;
+            }
+        }
+
+        for message in &fired {
+            log_info("Price Alert", message).unwrap_or(());
+            if let Err(e) = notifier::notify("Price alert", message) {
+                log_error("Notification Error", &e.to_string()).unwrap_or(());
+            }
+        }
+        if let Some(message) = fired.last() {
+            self.status_message = Some(format!("Alert: {}", message));
+        }
+        if changed {
+            if let Err(e) = self.save_config() {
+                log_error("Config Write Error", &e.to_string()).unwrap_or(());
+            }
+        }
+    }
+
+    /// Records the latest 24h changes of watched tokens and reports whether any of them
+    /// reached `bell_on_change_pct` since the previous refresh
+    pub fn check_large_moves(&mut self) -> bool {
+        let threshold = match self.config.bell_on_change_pct {
+            Some(threshold) => threshold,
+            None => return false,
+        };
+
+        let mut crossed = false;
+        let mut latest = HashMap::new();
+        for crypto in self.crypto_data.values() {
+            let watched = self.config.tokens.iter()
+                .any(|t| t.is_in_watchlist() && t.matches_crypto(crypto));
+            let change = match self.quote(crypto).and_then(|q| q.percent_change_24h) {
+                Some(change) if watched => change,
+                _ => continue,
+            };
+            // Nothing to compare against on the first refresh, so that one never rings
+            if let Some(previous) = self.last_changes.get(&crypto.symbol) {
+                if previous.abs() < threshold && change.abs() >= threshold {
+                    crossed = true;
+                }
+            }
+            latest.insert(crypto.symbol.clone(), change);
+        }
+
+        self.last_changes = latest;
+        crossed
+    }
+
+    /// Notifies when the newest fear & greed value has entered a different alert zone
+    /// than the one last notified about. Returns whether a notification was shown
+    pub fn check_fear_greed_alert(&mut self) -> bool {
+        let (alert, latest) = match (self.config.fear_greed_alert, self.fear_greed_data.first()) {
+            (Some(alert), Some(latest)) => (alert, latest),
+            _ => return false,
+        };
+        let zone = alert.zone(latest.value);
+        if zone == alert.notified {
+            return false;
+        }
+
+        if let Some(alert) = self.config.fear_greed_alert.as_mut() {
+            alert.notified = zone;
+        }
+        if let Err(e) = self.save_config() {
+            log_error("Config Save Error", &e.to_string()).unwrap_or(());
+        }
+
+        let label = match zone {
+            Some(FearGreedZone::Fear) => "extreme fear",
+            Some(FearGreedZone::Greed) => "extreme greed",
+            None => return false,
+        };
+        let message = format!("Fear & Greed index is in {} ({})", label, latest.value);
+        log_info("Fear & Greed Alert", &message).unwrap_or(());
+        if let Err(e) = notifier::notify("Fear & Greed alert", &message) {
+            log_error("Notification Error", &e.to_string()).unwrap_or(());
+        }
+        self.status_message = Some(format!("Alert: {}", message));
+        true
+    }
+
+    /// Appends the current total portfolio value to the value history
+    fn record_portfolio_value(&mut self, now: DateTime<Local>) {
+        let positions = self.real_positions();
+        if !positions.iter().any(|p| p.quote.is_some()) {
+            return;
+        }
+        let point = ValuePoint {
+            timestamp: now.timestamp(),
+            value: PortfolioTotals::from_positions(&positions).value,
+        };
+        match cache::append_value_snapshot(point) {
+            Ok(history) => self.value_history = history,
+            Err(e) => log_error("Cache Write Error", &e.to_string()).unwrap_or(()),
+        }
+    }
+
+    /// Seconds until the next automatic price refresh is due, based on the last
+    /// successful fetch. Zero or less means a fetch is due or in flight
+    pub fn seconds_until_refresh(&self) -> Option<i64> {
+        self.last_update.map(|time| {
+            self.config.price_refresh_interval as i64 - (Local::now() - time).num_seconds()
+        })
+    }
+
+    /// Age of the last successful fetch when it's older than `STALE_AFTER_INTERVALS`
+    /// refresh intervals, i.e. background fetches have been failing
+    pub fn stale_age(&self) -> Option<chrono::Duration> {
+        let age = Local::now() - self.last_update?;
+        let threshold = (self.config.price_refresh_interval * STALE_AFTER_INTERVALS) as i64;
+        (age.num_seconds() > threshold).then_some(age)
+    }
+
+    /// Returns the quote in the configured conversion currency, if CMC provided one
+    pub fn quote<'a>(&self, crypto: &'a CryptoData) -> Option<&'a Quote> {
+        crypto.quote.get(&self.config.convert_currency)
+    }
+
+    /// Returns the coins shown on the watchlist tab, filtered and in display order
+    pub fn watchlist_rows(&self) -> Vec<&CryptoData> {
+        let mut sorted_cryptos: Vec<_> = self.crypto_data.values()
+            .filter(|crypto| {
+                self.config.tokens.iter()
+                    .any(|token| token.is_in_watchlist() && token.matches_crypto(crypto))
+            })
+            .filter(|crypto| match self.filter.as_deref() {
+                Some(filter) if !filter.is_empty() => {
+                    let filter = filter.to_lowercase();
+                    crypto.symbol.to_lowercase().contains(&filter)
+                        || crypto.name.to_lowercase().contains(&filter)
+                        || self.token_for(crypto).is_some_and(|token| {
+                            token.tags.iter().any(|tag| tag.to_lowercase().contains(&filter))
+                        })
+                }
+                _ => true,
+            })
+            .collect();
+
+        sorted_cryptos.sort_by(|a, b| {
+            let (quote_a, quote_b) = match (self.quote(a), self.quote(b)) {
+                (Some(quote_a), Some(quote_b)) => (quote_a, quote_b),
+                // Coins without a quote in the configured currency always sink to the bottom
+                (Some(_), None) => return std::cmp::Ordering::Less,
+                (None, Some(_)) => return std::cmp::Ordering::Greater,
+                (None, None) => return a.symbol.cmp(&b.symbol),
+            };
+            let cmp = match self.sort_column {
+                SortColumn::Symbol => a.symbol.cmp(&b.symbol),
+                SortColumn::Price => quote_a.price.partial_cmp(&quote_b.price).unwrap_or(std::cmp::Ordering::Equal),
+                SortColumn::Change1h => quote_a.percent_change_1h.partial_cmp(&quote_b.percent_change_1h).unwrap_or(std::cmp::Ordering::Equal),
+                SortColumn::Change24h => quote_a.percent_change_24h.partial_cmp(&quote_b.percent_change_24h).unwrap_or(std::cmp::Ordering::Equal),
+                SortColumn::Change7d => quote_a.percent_change_7d.partial_cmp(&quote_b.percent_change_7d).unwrap_or(std::cmp::Ordering::Equal),
+                SortColumn::Change30d => quote_a.percent_change_30d.partial_cmp(&quote_b.percent_change_30d).unwrap_or(std::cmp::Ordering::Equal),
+                SortColumn::Change90d => quote_a.percent_change_90d.partial_cmp(&quote_b.percent_change_90d).unwrap_or(std::cmp::Ordering::Equal),
+                SortColumn::Volume24h => quote_a.volume_24h.partial_cmp(&quote_b.volume_24h).unwrap_or(std::cmp::Ordering::Equal),
+                SortColumn::VolumeChange => quote_a.volume_change_24h.partial_cmp(&quote_b.volume_change_24h).unwrap_or(std::cmp::Ordering::Equal),
+                SortColumn::MarketCap => quote_a.market_cap.partial_cmp(&quote_b.market_cap).unwrap_or(std::cmp::Ordering::Equal),
+                _ => std::cmp::Ordering::Equal, // Handle portfolio-specific columns
+            };
+            if self.watchlist_sort_ascending { cmp } else { cmp.reverse() }
+        });
+        // Stable sort, so pinned and unpinned rows each keep the order above
+        sorted_cryptos.sort_by_key(|crypto| !self.is_pinned(crypto));
+        // Sections in alphabetical order with ungrouped tokens last, each keeping the order above
+        if self.config.group_watchlist {
+            sorted_cryptos.sort_by_key(|crypto| {
+                let group = self.watchlist_group(crypto).map(str::to_lowercase);
+                (group.is_none(), group)
+            });
+        }
+
+        sorted_cryptos
+    }
+
+    /// The configured watchlist columns in display order, or all of them in the default
+    /// order when `columns` is empty. Repeated names are only shown once
+    pub fn watchlist_columns(&self) -> Vec<SortColumn> {
+        let names: Vec<&str> = if self.config.columns.is_empty() {
+            WATCHLIST_COLUMN_NAMES.to_vec()
+        } else {
+            self.config.columns.iter().map(String::as_str).collect()
+        };
+        let mut columns = Vec::with_capacity(names.len());
+        for column in names.into_iter().filter_map(SortColumn::from_column_name) {
+            if !columns.contains(&column) {
+                columns.push(column);
+            }
+        }
+        columns
+    }
+
+    /// Whether a watchlist column is shown in the current layout; the compact layout
+    /// drops the 30d/90d and volume change columns
+    pub fn is_column_visible(&self, column: SortColumn) -> bool {
+        !(self.compact_active
+            && matches!(column, SortColumn::Change30d | SortColumn::Change90d | SortColumn::VolumeChange))
+    }
+
+    /// The configured token a coin was fetched for
+    pub fn token_for(&self, crypto: &CryptoData) -> Option<&TokenConfig> {
+        self.config.tokens.iter().find(|token| token.matches_crypto(crypto))
+    }
+
+    /// The watchlist section a coin's token is assigned to, if any
+    pub fn watchlist_group(&self, crypto: &CryptoData) -> Option<&str> {
+        self.token_for(crypto).and_then(|token| token.group.as_deref())
+    }
+
+    /// Whether the coin belongs to a pinned token
+    pub fn is_pinned(&self, crypto: &CryptoData) -> bool {
+        self.config.tokens.iter().any(|token| token.pinned && token.matches_crypto(crypto))
+    }
+
+    /// The paper account, while paper trading is on
+    pub fn paper_account(&self) -> Option<&PaperAccount> {
+        self.config.paper.as_ref().filter(|_| self.paper_mode)
+    }
+
+    /// Returns the priced positions shown on the portfolio tab, in display order: the
+    /// paper account's while paper trading is on, the real holdings otherwise
+    pub fn portfolio_positions(&self) -> Vec<Position<'_>> {
+        match self.paper_account() {
+            Some(account) => self.positions_of(&account.positions),
+            None => self.real_positions(),
+        }
+    }
+
+    /// Whether the portfolio being shown has any tokens at all, priced or not
+    pub fn has_portfolio_tokens(&self) -> bool {
+        match self.paper_account() {
+            Some(account) => !account.positions.is_empty(),
+            None => self.config.tokens.iter().any(|token| token.is_in_portfolio()),
+        }
+    }
+
+    /// Positions of the real holdings, even while paper trading
+    pub fn real_positions(&self) -> Vec<Position<'_>> {
+        self.positions_of(&self.config.tokens)
+    }
+
+    fn positions_of<'a>(&'a self, tokens: &'a [TokenConfig]) -> Vec<Position<'a>> {
+        let mut positions: Vec<_> = tokens.iter()
+            .filter(|token| token.is_in_portfolio())
+            // Sold-out positions stay in the config but can be hidden
+            .filter(|token| !self.config.hide_empty_positions || token.owned.unwrap_or(0.0) > 0.0)
+            .filter_map(|token| {
+                self.crypto_data.values()
+                    .find(|crypto| token.matches_crypto(crypto))
+                    .map(|crypto| Position::new(token, crypto, self.quote(crypto), self.config.cost_basis_method))
+            })
+            .collect();
+
+        positions.sort_by(|a, b| {
+            let cmp = match self.portfolio_sort_column {
+                SortColumn::Symbol => a.crypto.symbol.cmp(&b.crypto.symbol),
+                SortColumn::Price => a.quote.map(|q| q.price).partial_cmp(&b.quote.map(|q| q.price)).unwrap_or(std::cmp::Ordering::Equal),
+                SortColumn::Holdings => a.holdings.partial_cmp(&b.holdings).unwrap_or(std::cmp::Ordering::Equal),
+                SortColumn::AvgBuy => a.avg_buy_price.partial_cmp(&b.avg_buy_price).unwrap_or(std::cmp::Ordering::Equal),
+                SortColumn::CurrentValue => a.current_value.partial_cmp(&b.current_value).unwrap_or(std::cmp::Ordering::Equal),
+                // Every position's share is taken of the same total, so this orders like value
+                // but keeps unpriced holdings (no allocation) at the bottom
+                SortColumn::Allocation => a.quote.map(|_| a.current_value)
+                    .partial_cmp(&b.quote.map(|_| b.current_value))
+                    .unwrap_or(std::cmp::Ordering::Equal),
+                SortColumn::CostBasis => a.cost_basis.partial_cmp(&b.cost_basis).unwrap_or(std::cmp::Ordering::Equal),
+                SortColumn::ProfitLoss => a.profit_loss.partial_cmp(&b.profit_loss).unwrap_or(std::cmp::Ordering::Equal),
+                SortColumn::ProfitLossPercent => a.profit_loss_pct.partial_cmp(&b.profit_loss_pct).unwrap_or(std::cmp::Ordering::Equal),
+                SortColumn::BreakEven => a.break_even_price.partial_cmp(&b.break_even_price).unwrap_or(std::cmp::Ordering::Equal),
+                // Distance from break-even in either direction, so ascending puts the closest first
+                SortColumn::ToBreakEven => a.to_break_even_pct.map(f64::abs)
+                    .partial_cmp(&b.to_break_even_pct.map(f64::abs))
+                    .unwrap_or(std::cmp::Ordering::Equal),
+                SortColumn::Change24h => a.quote.and_then(|q| q.percent_change_24h)
+                    .partial_cmp(&b.quote.and_then(|q| q.percent_change_24h))
+                    .unwrap_or(std::cmp::Ordering::Equal),
+                SortColumn::AnnualYield => a.annual_yield.partial_cmp(&b.annual_yield).unwrap_or(std::cmp::Ordering::Equal),
+                _ => std::cmp::Ordering::Equal,
+            };
+            if self.portfolio_sort_ascending { cmp } else { cmp.reverse() }
+        });
+
+        positions
     }
 
     pub async fn fetch_fear_greed(&self) -> Result<Vec<FearGreedData>> {
-        api::fetch_fear_greed(&self.config.api_key, &self.config.fear_and_greed_limit).await
+        self.provider.fetch_fear_greed(&self.config.fear_and_greed_limit).await
+    }
+
+    pub async fn fetch_global_metrics(&self) -> Result<GlobalMetrics> {
+        self.provider.fetch_global_metrics(&self.config.convert_currency).await
+    }
+
+    /// Symbols of the rows shown on the active tab, in display order
+    pub fn visible_symbols(&self) -> Vec<String> {
+        match self.tab_index {
+            0 => self.watchlist_rows().iter().map(|crypto| crypto.symbol.clone()).collect(),
+            1 => self.portfolio_positions().iter().map(|p| p.crypto.symbol.clone()).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Symbol of the currently highlighted row on the active tab
+    pub fn selected_symbol(&self) -> Option<String> {
+        self.table_state.selected()
+            .and_then(|i| self.visible_symbols().into_iter().nth(i))
+    }
+
+    /// Moves the selected portfolio position's holdings by `steps` times `quantity_step`
+    /// (never below zero) and saves the config right away
+    pub fn adjust_selected_holdings(&mut self, steps: f64) {
+        if self.tab_index != 1 {
+            return;
+        }
+        if self.paper_mode {
+            self.last_error = Some("Paper positions only change through buy and sell".to_string());
+            return;
+        }
+        let selected = self.table_state.selected()
+            .and_then(|i| self.portfolio_positions().into_iter().nth(i))
+            .and_then(|position| {
+                let token = self.token_for(position.crypto)?;
+                Some((token.name.clone(), position.crypto.symbol.clone(), position.holdings))
+            });
+        let (name, symbol, holdings) = match selected {
+            Some(selected) => selected,
+            None => return,
+        };
+
+        // Round off float noise so repeated 0.1 steps don't end in ...0000001
+        let owned = ((holdings + steps * self.config.quantity_step).max(0.0) * 1e8).round() / 1e8;
+        if let Err(message) = self.config.set_holdings(&name, Some(owned), None) {
+            self.last_error = Some(message);
+            return;
+        }
+        match self.save_config() {
+            Ok(()) => self.status_message = Some(format!(
+                "{} holdings set to {}", symbol, formatters::format_holdings(owned, self.config.holdings_precision))),
+            Err(e) => self.last_error = Some(format!("Failed to save config: {}", e)),
+        }
+    }
+
+    /// Copies the selected coin's symbol, optionally followed by its current price, to
+    /// the system clipboard
+    pub fn copy_selected(&mut self, with_price: bool) {
+        let symbol = match self.selected_symbol() {
+            Some(symbol) => symbol,
+            None => return,
+        };
+        let price = self.crypto_data.values()
+            .find(|crypto| crypto.symbol == symbol)
+            .and_then(|crypto| self.quote(crypto))
+            .map(|quote| quote.price);
+        let text = match price {
+            Some(price) if with_price => format!("{} {}", symbol, price),
+            _ => symbol.clone(),
+        };
+
+        match clipboard::copy(&text) {
+            Ok(()) => self.status_message = Some(format!("Copied {}", text)),
+            Err(e) => self.last_error = Some(format!("Clipboard unavailable: {}", e)),
+        }
     }
 
     pub fn next(&mut self) {
+        let count = self.visible_symbols().len();
+        // Nothing to select until the first price fetch lands
+        if count == 0 {
+            self.select_index(None);
+            return;
+        }
+
         let i = match self.table_state.selected() {
             Some(i) => {
-                if i >= self.crypto_data.len() - 1 {
+                if i >= count - 1 {
                     0
                 } else {
                     i + 1
@@ -118,100 +996,420 @@ impl App {
             }
             None => 0,
         };
-        self.table_state.select(Some(i));
+        self.select_index(Some(i));
     }
 
     pub fn previous(&mut self) {
+        let count = self.visible_symbols().len();
+        if count == 0 {
+            self.select_index(None);
+            return;
+        }
+
         let i = match self.table_state.selected() {
             Some(i) => {
-                if i == 0 {
-                    self.crypto_data.len() - 1
+                if i == 0 || i >= count {
+                    count - 1
                 } else {
                     i - 1
                 }
             }
             None => 0,
         };
-        self.table_state.select(Some(i));
+        self.select_index(Some(i));
+    }
+
+    /// Moves the selection by `delta` rows, stopping at the first/last row
+    fn move_selection(&mut self, delta: isize) {
+        let count = self.visible_symbols().len();
+        if count == 0 {
+            self.select_index(None);
+            return;
+        }
+
+        let current = self.table_state.selected().unwrap_or(0) as isize;
+        let target = (current + delta).clamp(0, count as isize - 1);
+        self.select_index(Some(target as usize));
+    }
+
+    pub fn page_down(&mut self) {
+        self.move_selection(self.page_size as isize);
+    }
+
+    pub fn page_up(&mut self) {
+        self.move_selection(-(self.page_size as isize));
+    }
+
+    pub fn select_first(&mut self) {
+        self.move_selection(isize::MIN / 2);
+    }
+
+    pub fn select_last(&mut self) {
+        self.move_selection(isize::MAX / 2);
+    }
+
+    fn select_index(&mut self, index: Option<usize>) {
+        self.table_state.select(index);
+        self.selected_coin = self.selected_symbol();
+    }
+
+    /// Re-resolves the highlighted row after the rows change (new prices, sorting,
+    /// filtering or switching tabs): the selected coin stays highlighted wherever it
+    /// moved, and if it's gone the selection falls back to the nearest valid row
+    pub fn restore_selection(&mut self) {
+        let symbols = self.visible_symbols();
+        let position = self.selected_coin.as_ref()
+            .and_then(|coin| symbols.iter().position(|symbol| symbol == coin));
+        let index = match (position, self.table_state.selected()) {
+            _ if symbols.is_empty() => None,
+            (Some(i), _) => Some(i),
+            (None, Some(i)) => Some(i.min(symbols.len() - 1)),
+            (None, None) => None,
+        };
+        self.select_index(index);
     }
 
     pub fn next_tab(&mut self) {
         self.tab_index = (self.tab_index + 1) % 3;
+        self.restore_selection();
+    }
+
+    pub fn previous_tab(&mut self) {
+        self.tab_index = (self.tab_index + 2) % 3;
+        self.restore_selection();
+    }
+
+    pub fn select_tab(&mut self, index: usize) {
+        if index < 3 {
+            self.tab_index = index;
+            self.restore_selection();
+        }
     }
 
     pub async fn process_command(&mut self) -> Result<()> {
+        self.record_command();
+        // An earlier error no longer applies once another command runs; this one sets
+        // its own if it fails
+        self.last_error = None;
         let command = self.parse_command();
         match command {
             Command::Add { name, watchlist, portfolio, owned, avg_buy_price } => {
-                // Update config
+                // A price far off the market is usually a misplaced decimal, so ask first
+                match self.avg_price_warning(&name, avg_buy_price) {
+                    Some(prompt) => self.request_confirmation(
+                        prompt,
+                        Command::Add { name, watchlist, portfolio, owned, avg_buy_price },
+                    ),
+                    None => self.add_token(&name, watchlist, portfolio, owned, avg_buy_price).await?,
+                }
+            }
+            Command::Remove { name, watchlist, portfolio } => {
+                // Dropping portfolio data loses cost basis, so ask first
+                let has_holdings = portfolio && self.config.tokens.iter()
+                    .any(|t| t.name.to_lowercase() == name.to_lowercase() && t.is_in_portfolio());
+
+                if has_holdings {
+                    self.request_confirmation(
+                        format!("Remove {} from portfolio? (y/n)", name.to_uppercase()),
+                        Command::Remove { name, watchlist, portfolio },
+                    );
+                } else {
+                    self.remove_token(&name, watchlist, portfolio).await?;
+                }
+            }
+            Command::Set { name, owned, avg_buy_price } => {
+                match self.avg_price_warning(&name, avg_buy_price) {
+                    Some(prompt) => self.request_confirmation(prompt, Command::Set { name, owned, avg_buy_price }),
+                    None => self.set_holdings(&name, owned, avg_buy_price).await?,
+                }
+            }
+            Command::FearGreedLimit { days } => {
+                self.config.fear_and_greed_limit = days.to_string();
+                self.save_config()?;
+
+                match self.fetch_fear_greed().await {
+                    Ok(data) => {
+                        self.fear_greed_data = data;
+                        self.fear_greed_error = None;
+                        self.status_message = Some(format!("Fear & Greed window set to {} days", days));
+                    }
+                    Err(e) => self.fear_greed_error = Some(api::describe_error(&e)),
+                }
+            }
+            Command::FearGreedAlert(thresholds) => {
+                self.config.fear_greed_alert = thresholds.map(|(fear, greed)| FearGreedAlert {
+                    fear,
+                    greed,
+                    notified: None,
+                });
+                self.save_config()?;
+                self.status_message = Some(match thresholds {
+                    Some((fear, greed)) => format!(
+                        "Fear & Greed alert set: at or below {}, at or above {}", fear, greed),
+                    None => "Fear & Greed alert off".to_string(),
+                });
+                self.check_fear_greed_alert();
+            }
+            Command::Paper { reset: true } => {
+                let account = self.config.paper.get_or_insert_with(PaperAccount::default);
+                account.reset();
+                let cash = account.starting_cash;
+                self.save_config()?;
+                self.status_message = Some(format!(
+                    "Paper account reset to {}", formatters::format_amount(cash, &self.config.convert_currency)));
+            }
+            Command::Paper { reset: false } => {
+                self.paper_mode = !self.paper_mode;
+                if self.paper_mode && self.config.paper.is_none() {
+                    self.config.paper = Some(PaperAccount::default());
+                    self.save_config()?;
+                }
+                self.status_message = Some(if self.paper_mode {
+                    "Paper trading on: buy and sell are simulated".to_string()
+                } else {
+                    "Paper trading off".to_string()
+                });
+                self.restore_selection();
+            }
+            Command::Reload => {
+                // Keep running on the current config if the file on disk is broken
+                let mut config = match Config::load(&self.config_path) {
+                    Ok(config) => config,
+                    Err(e) => {
+                        self.last_error = Some(format!("Reload failed: {:#}", e));
+                        return Ok(());
+                    }
+                };
+                let merged = config.merge_duplicate_tokens();
+                if let Err(errors) = config.validate() {
+                    self.last_error = Some(format!("Reload failed: {}", errors.join("; ")));
+                    return Ok(());
+                }
+                // Pick up edited timeout and proxy settings for every request from here on
+                if let Err(e) = api::init_client(config.request_timeout_secs, config.resolved_https_proxy().as_deref()) {
+                    self.last_error = Some(format!("Reload failed: {:#}", e));
+                    return Ok(());
+                }
+
+                self.theme = Theme::from_name(config.theme.as_deref());
+                formatters::set_number_locale(NumberLocale::from_name(config.number_locale.as_deref()));
+                self.provider = provider::from_config(&config);
+                self.config = config;
+                if merged > 0 {
+                    self.save_config()?;
+                }
+                self.last_error = None;
+                self.status_message = Some(format!("Reloaded {}", self.config_path.display()));
+
+                // The background polls restart on the new config and refresh right away
+                self.reloads += 1;
+                self.is_loading = true;
+            }
+            Command::DryRun(command) => self.preview_command(*command),
+            Command::Trade { name, kind, quantity, price } => {
+                let transaction = Transaction {
+                    kind,
+                    quantity,
+                    price,
+                    timestamp: Local::now().timestamp(),
+                };
+                let method = self.config.cost_basis_method;
+                if self.paper_mode {
+                    let account = self.config.paper.get_or_insert_with(PaperAccount::default);
+                    if let Err(message) = account.trade(&name, transaction, method) {
+                        self.last_error = Some(message);
+                        return Ok(());
+                    }
+                    self.save_config()?;
+                    if let Ok(new_data) = self.fetch_prices().await {
+                        self.set_prices(new_data);
+                    }
+                    return Ok(());
+                }
                 let token = self.config.tokens.iter_mut()
                     .find(|t| t.name.to_lowercase() == name.to_lowercase());
 
-                match token {
-                    Some(token) => {
-                        // Update existing token
-                        if watchlist {
-                            token.in_watchlist = true;
-                        }
-                        if portfolio {
-                            token.in_portfolio = true;
-                            if let Some(owned) = owned {
-                                token.owned = Some(owned);
-                            }
-                            if let Some(price) = avg_buy_price {
-                                token.avg_buy_price = Some(price);
-                            }
-                        }
+                match (token, kind) {
+                    (Some(token), TransactionKind::Sell) if quantity > token.owned.unwrap_or(0.0) => {
+                        self.last_error = Some(format!(
+                            "Cannot sell {} {}, only {} held", quantity, name, token.owned.unwrap_or(0.0)));
+                        return Ok(());
                     }
-                    None => {
-                        // Add new token
-                        self.config.tokens.push(TokenConfig {
+                    (Some(token), _) => {
+                        token.in_portfolio = true;
+                        token.record_transaction(transaction, method);
+                    }
+                    (None, TransactionKind::Buy) => {
+                        let mut token = TokenConfig {
                             name,
-                            owned,
-                            avg_buy_price,
-                            in_watchlist: watchlist,
-                            in_portfolio: portfolio,
-                        });
+                            owned: None,
+                            avg_buy_price: None,
+                            in_watchlist: false,
+                            in_portfolio: true,
+                            pinned: false,
+                            transactions: Vec::new(),
+                            target_allocation: None,
+                            staking_apr: None,
+                            tags: Vec::new(),
+                            note: None,
+                            group: None,
+                        };
+                        token.record_transaction(transaction, method);
+                        self.config.tokens.push(token);
+                    }
+                    (None, TransactionKind::Sell) => {
+                        self.last_error = Some(format!("Token '{}' not found", name));
+                        return Ok(());
                     }
                 }
 
-                // Save config
-                let config_str = serde_json::to_string_pretty(&self.config)?;
-                std::fs::write("config.json", config_str)?;
+                self.save_config()?;
 
                 // Refresh data
                 if let Ok(new_data) = self.fetch_prices().await {
-                    self.crypto_data = new_data;
-                    self.last_update = Some(Local::now());
+                    self.set_prices(new_data);
                 }
             }
-            Command::Remove { name, watchlist, portfolio } => {
-                if let Some(token) = self.config.tokens.iter_mut()
-                    .find(|t| t.name.to_lowercase() == name.to_lowercase())
-                {
-                    if watchlist {
-                        token.in_watchlist = false;
+            Command::Alert { name, direction, price } => {
+                if !self.config.tokens.iter().any(|t| t.name.to_lowercase() == name.to_lowercase()) {
+                    self.last_error = Some(format!(
+                        "Token '{}' not found. Use add <name> -w to track it first", name));
+                    return Ok(());
+                }
+
+                self.config.alerts.push(Alert {
+                    token: name.clone(),
+                    direction,
+                    price,
+                    triggered: false,
+                });
+                self.save_config()?;
+                self.status_message = Some(format!(
+                    "Alert set: {} {} {}",
+                    name,
+                    if direction == AlertDirection::Above { "above" } else { "below" },
+                    format_price(price, &self.config.convert_currency),
+                ));
+            }
+            Command::Top { n } => {
+                let listings = match self.provider.fetch_listings(n, &self.config.convert_currency).await {
+                    Ok(listings) => listings,
+                    Err(e) => {
+                        self.last_error = Some(format!("Failed to fetch top coins: {}", api::describe_error(&e)));
+                        return Ok(());
                     }
-                    if portfolio {
-                        token.in_portfolio = false;
-                        token.owned = None;
-                        token.avg_buy_price = None;
+                };
+
+                let mut added = 0;
+                for crypto in &listings {
+                    let slug = match &crypto.slug {
+                        Some(slug) => slug,
+                        None => continue,
+                    };
+                    // Existing entries (including portfolio ones) only get the watchlist flag
+                    match self.config.tokens.iter_mut().find(|t| t.matches_crypto(crypto)) {
+                        Some(token) => token.in_watchlist = true,
+                        None => {
+                            self.config.tokens.push(TokenConfig {
+                                name: slug.clone(),
+                                owned: None,
+                                avg_buy_price: None,
+                                in_watchlist: true,
+                                in_portfolio: false,
+                                pinned: false,
+                                transactions: Vec::new(),
+                                target_allocation: None,
+                                staking_apr: None,
+                                tags: Vec::new(),
+                                note: None,
+                                group: None,
+                            });
+                            added += 1;
+                        }
                     }
                 }
 
-                // Remove token completely if neither in watchlist nor portfolio
-                self.config.tokens.retain(|t| t.in_watchlist || t.in_portfolio);
-
-                // Save config
-                let config_str = serde_json::to_string_pretty(&self.config)?;
-                std::fs::write("config.json", config_str)?;
+                self.save_config()?;
+                self.status_message = Some(format!(
+                    "Watching the top {} coins ({} new)", listings.len(), added));
 
                 // Refresh data
                 if let Ok(new_data) = self.fetch_prices().await {
-                    self.crypto_data = new_data;
-                    self.last_update = Some(Local::now());
+                    self.set_prices(new_data);
                 }
             }
+            Command::Pin { name, pinned } => {
+                match self.config.tokens.iter_mut().find(|t| t.name.to_lowercase() == name.to_lowercase()) {
+                    Some(token) => token.pinned = pinned,
+                    None => {
+                        self.last_error = Some(format!("Token '{}' not found", name));
+                        return Ok(());
+                    }
+                }
+                self.save_config()?;
+                self.restore_selection();
+            }
+            Command::Tag { name, tags } => {
+                match self.config.tokens.iter_mut().find(|t| t.name.to_lowercase() == name.to_lowercase()) {
+                    Some(token) => token.tags = tags.clone(),
+                    None => {
+                        self.last_error = Some(format!("Token '{}' not found", name));
+                        return Ok(());
+                    }
+                }
+                self.save_config()?;
+                self.status_message = Some(if tags.is_empty() {
+                    format!("Cleared the tags of {}", name)
+                } else {
+                    format!("Tagged {}: {}", name, tags.join(", "))
+                });
+            }
+            Command::Note { name, note } => {
+                match self.config.tokens.iter_mut().find(|t| t.name.to_lowercase() == name.to_lowercase()) {
+                    Some(token) => token.note = note,
+                    None => {
+                        self.last_error = Some(format!("Token '{}' not found", name));
+                        return Ok(());
+                    }
+                }
+                self.save_config()?;
+            }
+            Command::Export { path } => {
+                let count = self.export_portfolio(&path)?;
+                self.status_message = Some(format!(
+                    "Exported {} {}positions to {}",
+                    count, if self.paper_mode { "real " } else { "" }, path.display()));
+            }
+            Command::Import { path } => {
+                let PortfolioImport { imported, skipped } = match self.import_portfolio(&path) {
+                    Ok(result) => result,
+                    Err(e) => {
+                        self.last_error = Some(format!("Import failed: {:#}", e));
+                        return Ok(());
+                    }
+                };
+
+                // Refresh first, so the imported avg prices can be checked against the market
+                if !imported.is_empty() {
+                    self.save_config()?;
+                    if let Ok(new_data) = self.fetch_prices().await {
+                        self.set_prices(new_data);
+                    }
+                }
+
+                let (suspicious, unchecked) = self.check_imported_prices(&imported);
+                let mut message = format!("Imported {} positions from {}", imported.len(), path.display());
+                if !skipped.is_empty() {
+                    message.push_str(&format!("; skipped {}", skipped.join(", ")));
+                }
+                if !suspicious.is_empty() {
+                    message.push_str(&format!("; check avg price of {}", suspicious.join(", ")));
+                }
+                if unchecked > 0 {
+                    message.push_str(&format!("; {} avg prices not checked (no market price)", unchecked));
+                }
+                self.status_message = Some(message);
+            }
             Command::Invalid(msg) => {
                 self.last_error = Some(msg);
             }
@@ -219,12 +1417,266 @@ impl App {
         Ok(())
     }
 
+    /// Adds or updates a portfolio token for every `name,owned,avg_buy_price` row of a
+    /// CSV file. An optional header row and blank lines are ignored; rows that don't
+    /// parse are skipped and described next to the imported rows
+    fn import_portfolio(&mut self, path: &Path) -> Result<PortfolioImport> {
+        let csv = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+
+        let mut imported = Vec::new();
+        let mut skipped = Vec::new();
+        for (i, line) in csv.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || (i == 0 && line.to_lowercase().starts_with("name")) {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let row = match fields.as_slice() {
+                [name, owned, avg] if !name.is_empty() => {
+                    match (owned.parse::<f64>(), avg.parse::<f64>()) {
+                        (Ok(owned), Ok(avg)) if owned.is_finite() && owned >= 0.0 && avg.is_finite() && avg >= 0.0 => {
+                            self.config.add_token(name, false, true, Some(owned), Some(avg))
+                                .map(|()| imported.push((name.to_string(), avg)))
+                        }
+                        _ => Err("owned and avg_buy_price must be non-negative numbers".to_string()),
+                    }
+                }
+                _ => Err("expected name,owned,avg_buy_price".to_string()),
+            };
+
+            if let Err(e) = row {
+                skipped.push(format!("line {} ({})", i + 1, e));
+            }
+        }
+        Ok(PortfolioImport { imported, skipped })
+    }
+
+    /// Describes the imported avg buy prices more than `AVG_PRICE_SANITY_RATIO` times
+    /// off the market price, and counts the ones without a market price to check against
+    fn check_imported_prices(&self, imported: &[(String, f64)]) -> (Vec<String>, usize) {
+        let mut suspicious = Vec::new();
+        let mut unchecked = 0;
+        for (name, avg_buy_price) in imported.iter().filter(|(_, avg)| *avg > 0.0) {
+            match self.market_price(name) {
+                Some(market_price) => {
+                    let ratio = price_ratio(*avg_buy_price, market_price);
+                    if ratio > AVG_PRICE_SANITY_RATIO {
+                        suspicious.push(format!(
+                            "{} ({:.0}x {} market)",
+                            name,
+                            ratio,
+                            if *avg_buy_price > market_price { "above" } else { "below" },
+                        ));
+                    }
+                }
+                None => unchecked += 1,
+            }
+        }
+        (suspicious, unchecked)
+    }
+
+    /// Writes one CSV row per priced portfolio position, using the same figures
+    /// as the portfolio table. Always the real holdings, never the paper account, so
+    /// an export can't pass simulated positions off as real ones. Returns the number
+    /// of positions written
+    fn export_portfolio(&self, path: &Path) -> Result<usize> {
+        let positions = self.real_positions();
+
+        let mut csv = String::from(
+            "symbol,holdings,avg_buy_price,current_price,current_value,cost_basis,profit_loss,profit_loss_pct\n");
+        for position in &positions {
+            // Unpriced holdings keep their cost basis but leave market figures empty
+            let (price, value, pl, pl_pct) = match position.quote {
+                Some(quote) => (
+                    quote.price.to_string(),
+                    position.current_value.to_string(),
+                    position.profit_loss.to_string(),
+                    format!("{:.2}", position.profit_loss_pct),
+                ),
+                None => Default::default(),
+            };
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{}\n",
+                position.crypto.symbol,
+                position.holdings,
+                position.avg_buy_price,
+                price,
+                value,
+                position.cost_basis,
+                pl,
+                pl_pct,
+            ));
+        }
+
+        std::fs::write(path, csv)?;
+        Ok(positions.len())
+    }
+
+    async fn add_token(
+        &mut self,
+        name: &str,
+        watchlist: bool,
+        portfolio: bool,
+        owned: Option<f64>,
+        avg_buy_price: Option<f64>,
+    ) -> Result<()> {
+        if let Err(message) = self.config.add_token(name, watchlist, portfolio, owned, avg_buy_price) {
+            self.last_error = Some(message);
+            return Ok(());
+        }
+
+        self.save_config()?;
+
+        // Refresh data
+        if let Ok(new_data) = self.fetch_prices().await {
+            self.set_prices(new_data);
+        }
+        Ok(())
+    }
+
+    async fn set_holdings(&mut self, name: &str, owned: Option<f64>, avg_buy_price: Option<f64>) -> Result<()> {
+        if let Err(message) = self.config.set_holdings(name, owned, avg_buy_price) {
+            self.last_error = Some(message);
+            return Ok(());
+        }
+
+        self.save_config()?;
+
+        // Refresh data
+        if let Ok(new_data) = self.fetch_prices().await {
+            self.set_prices(new_data);
+        }
+        Ok(())
+    }
+
+    /// A confirmation prompt when `avg_buy_price` is more than `AVG_PRICE_SANITY_RATIO`
+    /// times above or below the last fetched price of the token named `name`. Tokens
+    /// without a price yet aren't checked
+    fn avg_price_warning(&self, name: &str, avg_buy_price: Option<f64>) -> Option<String> {
+        let avg_buy_price = avg_buy_price.filter(|price| *price > 0.0)?;
+        let market_price = self.market_price(name)?;
+
+        let ratio = price_ratio(avg_buy_price, market_price);
+        (ratio > AVG_PRICE_SANITY_RATIO).then(|| {
+            let currency = &self.config.convert_currency;
+            format!(
+                "Avg price {} is {:.0}x {} the market price {} for {}. Save anyway? (y/n)",
+                format_price(avg_buy_price, currency),
+                ratio,
+                if avg_buy_price > market_price { "above" } else { "below" },
+                format_price(market_price, currency),
+                name.to_uppercase(),
+            )
+        })
+    }
+
+    /// Last fetched price of the configured token named `name`, if it has a positive one
+    fn market_price(&self, name: &str) -> Option<f64> {
+        let token = self.config.tokens.iter().find(|token| token.name.eq_ignore_ascii_case(name))?;
+        self.crypto_data.values()
+            .find(|crypto| token.matches_crypto(crypto))
+            .and_then(|crypto| self.quote(crypto))
+            .map(|quote| quote.price)
+            .filter(|price| *price > 0.0)
+    }
+
+    async fn remove_token(&mut self, name: &str, watchlist: bool, portfolio: bool) -> Result<()> {
+        self.config.remove_token(name, watchlist, portfolio);
+
+        self.save_config()?;
+
+        // Refresh data
+        if let Ok(new_data) = self.fetch_prices().await {
+            self.set_prices(new_data);
+        }
+        Ok(())
+    }
+
+    /// Applies add/rm/set to a copy of the config and reports what would change
+    fn preview_command(&mut self, command: Command) {
+        let mut preview = self.config.clone();
+        let result = match command {
+            Command::Add { name, watchlist, portfolio, owned, avg_buy_price } => {
+                preview.add_token(&name, watchlist, portfolio, owned, avg_buy_price)
+            }
+            Command::Remove { name, watchlist, portfolio } => {
+                preview.remove_token(&name, watchlist, portfolio);
+                Ok(())
+            }
+            Command::Set { name, owned, avg_buy_price } => preview.set_holdings(&name, owned, avg_buy_price),
+            _ => Err("--dry-run is only supported for add, rm and set".to_string()),
+        };
+
+        match result {
+            Ok(()) => {
+                let changes = self.config.token_changes(&preview);
+                self.status_message = Some(if changes.is_empty() {
+                    "Dry run: no changes".to_string()
+                } else {
+                    format!("Dry run: {}", changes.join("; "))
+                });
+            }
+            Err(message) => self.last_error = Some(message),
+        }
+    }
+
+    /// Parks a command until the user answers the y/n prompt in the input area
+    fn request_confirmation(&mut self, prompt: String, command: Command) {
+        self.pending_confirmation = Some(PendingConfirmation { prompt, command });
+        self.input_mode = InputMode::Confirm;
+    }
+
+    /// Runs the command waiting for confirmation
+    pub async fn confirm_pending(&mut self) -> Result<()> {
+        self.input_mode = InputMode::Normal;
+        if let Some(pending) = self.pending_confirmation.take() {
+            match pending.command {
+                Command::Remove { name, watchlist, portfolio } => {
+                    self.remove_token(&name, watchlist, portfolio).await?;
+                }
+                Command::Add { name, watchlist, portfolio, owned, avg_buy_price } => {
+                    self.add_token(&name, watchlist, portfolio, owned, avg_buy_price).await?;
+                }
+                Command::Set { name, owned, avg_buy_price } => {
+                    self.set_holdings(&name, owned, avg_buy_price).await?;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    pub fn cancel_pending(&mut self) {
+        self.pending_confirmation = None;
+        self.input_mode = InputMode::Normal;
+    }
+
+    fn save_config(&self) -> Result<()> {
+        self.config.save(&self.config_path)
+    }
+
     fn parse_command(&self) -> Command {
-        let parts: Vec<&str> = self.input.split_whitespace().collect();
+        let mut parts: Vec<&str> = self.input.split_whitespace().collect();
+        let dry_run = parts.contains(&"--dry-run");
+        parts.retain(|part| *part != "--dry-run");
         if parts.is_empty() {
             return Command::Invalid("Empty command".to_string());
         }
 
+        match Self::parse_parts(&parts) {
+            command @ (Command::Add { .. } | Command::Remove { .. } | Command::Set { .. }) if dry_run => {
+                Command::DryRun(Box::new(command))
+            }
+            Command::Invalid(message) => Command::Invalid(message),
+            _ if dry_run => Command::Invalid("--dry-run is only supported for add, rm and set".to_string()),
+            command => command,
+        }
+    }
+
+    fn parse_parts(parts: &[&str]) -> Command {
+
         match parts[0] {
             "add" => {
                 if parts.len() < 2 {
@@ -307,7 +1759,287 @@ impl App {
                     portfolio,
                 }
             }
-            _ => Command::Invalid("Unknown command. Available commands: add, rm".to_string()),
+            "set" => {
+                let usage = "Usage: set <name> [owned <amount>] [avg <price>]";
+                if parts.len() < 4 {
+                    return Command::Invalid(usage.to_string());
+                }
+
+                let name = parts[1].to_string();
+                let mut owned = None;
+                let mut avg_buy_price = None;
+
+                for pair in parts[2..].chunks(2) {
+                    let value = match pair.get(1).and_then(|v| v.parse::<f64>().ok()) {
+                        Some(value) if value >= 0.0 => value,
+                        _ => return Command::Invalid(usage.to_string()),
+                    };
+                    match pair[0] {
+                        "owned" => owned = Some(value),
+                        "avg" => avg_buy_price = Some(value),
+                        _ => return Command::Invalid(usage.to_string()),
+                    }
+                }
+
+                Command::Set {
+                    name,
+                    owned,
+                    avg_buy_price,
+                }
+            }
+            "buy" | "sell" => {
+                let usage = format!("Usage: {} <name> <quantity> <price>", parts[0]);
+                if parts.len() != 4 {
+                    return Command::Invalid(usage);
+                }
+
+                let quantity = match parts[2].parse::<f64>() {
+                    Ok(quantity) if quantity > 0.0 => quantity,
+                    _ => return Command::Invalid(usage),
+                };
+                let price = match parts[3].parse::<f64>() {
+                    Ok(price) if price >= 0.0 => price,
+                    _ => return Command::Invalid(usage),
+                };
+
+                Command::Trade {
+                    name: parts[1].to_string(),
+                    kind: if parts[0] == "buy" { TransactionKind::Buy } else { TransactionKind::Sell },
+                    quantity,
+                    price,
+                }
+            }
+            "alert" => {
+                let usage = "Usage: alert <name> above|below <price>";
+                if parts.len() != 4 {
+                    return Command::Invalid(usage.to_string());
+                }
+
+                let direction = match parts[2] {
+                    "above" => AlertDirection::Above,
+                    "below" => AlertDirection::Below,
+                    _ => return Command::Invalid(usage.to_string()),
+                };
+                let price = match parts[3].parse::<f64>() {
+                    Ok(price) if price > 0.0 => price,
+                    _ => return Command::Invalid(usage.to_string()),
+                };
+
+                Command::Alert {
+                    name: parts[1].to_string(),
+                    direction,
+                    price,
+                }
+            }
+            "top" => {
+                let usage = format!("Usage: top <n> (1-{})", api::MAX_LISTINGS_LIMIT);
+                match parts.get(1).map(|n| n.parse::<u32>()) {
+                    Some(Ok(n)) if parts.len() == 2 && n > 0 => Command::Top {
+                        n: n.min(api::MAX_LISTINGS_LIMIT),
+                    },
+                    _ => Command::Invalid(usage),
+                }
+            }
+            "pin" | "unpin" => {
+                if parts.len() != 2 {
+                    return Command::Invalid(format!("Usage: {} <name>", parts[0]));
+                }
+
+                Command::Pin {
+                    name: parts[1].to_string(),
+                    pinned: parts[0] == "pin",
+                }
+            }
+            "tag" => {
+                if parts.len() < 2 {
+                    return Command::Invalid("Usage: tag <token-name> [tag...]".to_string());
+                }
+
+                let mut tags: Vec<String> = Vec::new();
+                for tag in &parts[2..] {
+                    if !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                        tags.push(tag.to_string());
+                    }
+                }
+                Command::Tag {
+                    name: parts[1].to_string(),
+                    tags,
+                }
+            }
+            "note" => {
+                if parts.len() < 2 {
+                    return Command::Invalid("Usage: note <token-name> [text]".to_string());
+                }
+
+                Command::Note {
+                    name: parts[1].to_string(),
+                    note: (parts.len() > 2).then(|| parts[2..].join(" ")),
+                }
+            }
+            "fng" => {
+                let usage = format!("Usage: fng <days> (1-{})", api::MAX_FEAR_GREED_LIMIT);
+                match parts.get(1).map(|days| days.parse::<u32>()) {
+                    Some(Ok(days)) if parts.len() == 2 && days > 0 => Command::FearGreedLimit {
+                        days: days.min(api::MAX_FEAR_GREED_LIMIT),
+                    },
+                    _ => Command::Invalid(usage),
+                }
+            }
+            "fg-alert" => {
+                let usage = "Usage: fg-alert <fear> <greed> (0-100) or fg-alert off";
+                match parts[1..] {
+                    ["off"] => Command::FearGreedAlert(None),
+                    [fear, greed] => match (fear.parse::<u64>(), greed.parse::<u64>()) {
+                        (Ok(fear), Ok(greed)) if fear < greed && greed <= 100 => {
+                            Command::FearGreedAlert(Some((fear, greed)))
+                        }
+                        _ => Command::Invalid(usage.to_string()),
+                    },
+                    _ => Command::Invalid(usage.to_string()),
+                }
+            }
+            "paper" => match parts[1..] {
+                [] => Command::Paper { reset: false },
+                ["reset"] => Command::Paper { reset: true },
+                _ => Command::Invalid("Usage: paper or paper reset".to_string()),
+            },
+            "reload" => {
+                if parts.len() != 1 {
+                    return Command::Invalid("Usage: reload".to_string());
+                }
+
+                Command::Reload
+            }
+            "export" => {
+                if parts.len() != 2 {
+                    return Command::Invalid("Usage: export <path>".to_string());
+                }
+
+                Command::Export {
+                    path: PathBuf::from(parts[1]),
+                }
+            }
+            "import" => {
+                if parts.len() != 2 {
+                    return Command::Invalid("Usage: import <path>".to_string());
+                }
+
+                Command::Import {
+                    path: PathBuf::from(parts[1]),
+                }
+            }
+            _ => Command::Invalid("Unknown command. Available commands: add, rm, set, buy, sell, alert, top, pin, unpin, tag, note, export, import, fng, fg-alert, paper, reload".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::config::CostBasisMethod;
+
+    fn empty_app() -> App {
+        let config: Config = serde_json::from_value(serde_json::json!({
+            "provider": "demo",
+            "api_key": "",
+            "fear_and_greed_limit": "30",
+            "tokens": [],
+        }))
+        .expect("minimal config should parse");
+        App::new(config, std::env::temp_dir().join("cointracker-state-tests.json"))
+    }
+
+    /// Writes `contents` to a CSV file in the temp dir, unique to this test process
+    fn write_csv(file_name: &str, contents: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("cointracker-state-tests-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("temp dir should be writable");
+        let path = dir.join(file_name);
+        std::fs::write(&path, contents).expect("csv should be writable");
+        path
+    }
+
+    fn holdings(app: &App, name: &str) -> (Option<f64>, Option<f64>) {
+        let token = app.config.tokens.iter().find(|token| token.name == name).expect("token should exist");
+        (token.owned, token.avg_buy_price)
+    }
+
+    #[test]
+    fn import_skips_malformed_rows() {
+        let mut app = empty_app();
+        let path = write_csv("malformed.csv", "name,owned,avg_buy_price\n\
+            bitcoin,0.5,30000\n\
+            \n\
+            ethereum,abc,100\n\
+            solana,1,-5\n\
+            dogecoin,100\n\
+            ,1,2\n\
+            cardano, 10 , 0.3\n");
+
+        let PortfolioImport { imported, skipped } = app.import_portfolio(&path).expect("csv should import");
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(imported, [("bitcoin".to_string(), 30000.0), ("cardano".to_string(), 0.3)]);
+        assert_eq!(skipped, [
+            "line 4 (owned and avg_buy_price must be non-negative numbers)",
+            "line 5 (owned and avg_buy_price must be non-negative numbers)",
+            "line 6 (expected name,owned,avg_buy_price)",
+            "line 7 (expected name,owned,avg_buy_price)",
+        ]);
+        assert_eq!(app.config.tokens.len(), 2);
+        assert_eq!(holdings(&app, "bitcoin"), (Some(0.5), Some(30000.0)));
+        assert_eq!(holdings(&app, "cardano"), (Some(10.0), Some(0.3)));
+    }
+
+    #[test]
+    fn import_duplicate_rows_update_one_token() {
+        let mut app = empty_app();
+        app.config.add_token("ethereum", false, true, None, None).expect("token should add");
+        app.config.tokens[0].record_transaction(Transaction {
+            kind: TransactionKind::Buy,
+            quantity: 1.0,
+            price: 2000.0,
+            timestamp: 1700000000,
+        }, CostBasisMethod::Average);
+        let path = write_csv("duplicates.csv", "bitcoin,1,20000\nBitcoin,2,30000\nethereum,5,100\n");
+
+        let PortfolioImport { imported, skipped } = app.import_portfolio(&path).expect("csv should import");
+        let _ = std::fs::remove_file(&path);
+
+        // The later row wins, and a token with a history keeps it
+        assert_eq!(imported.len(), 2);
+        assert_eq!(skipped, ["line 3 (ethereum has a transaction history. Use buy/sell to change holdings)"]);
+        assert_eq!(app.config.tokens.len(), 2);
+        assert_eq!(holdings(&app, "bitcoin"), (Some(2.0), Some(30000.0)));
+        assert_eq!(holdings(&app, "ethereum"), (Some(1.0), Some(2000.0)));
+    }
+
+    #[test]
+    fn import_flags_outlier_avg_prices() {
+        let mut app = empty_app();
+        let path = write_csv("outliers.csv", "bitcoin,1,300\nsolana,10,150\nethereum,2,3000\n");
+        let PortfolioImport { imported, .. } = app.import_portfolio(&path).expect("csv should import");
+        let _ = std::fs::remove_file(&path);
+        // Only bitcoin ($64,250) and solana ($148.60) have a market price
+        app.crypto_data = crate::services::demo::prices(&["bitcoin".to_string(), "solana".to_string()], "USD");
+
+        let (suspicious, unchecked) = app.check_imported_prices(&imported);
+        assert_eq!(suspicious, ["bitcoin (214x below market)"]);
+        assert_eq!(unchecked, 1);
+        assert!(app.avg_price_warning("bitcoin", Some(300.0)).is_some());
+        assert!(app.avg_price_warning("solana", Some(150.0)).is_none());
+        assert!(app.avg_price_warning("ethereum", Some(3000.0)).is_none());
+    }
+
+    #[test]
+    fn navigation_without_rows_selects_nothing() {
+        let mut app = empty_app();
+        for tab in [0, 1] {
+            app.tab_index = tab;
+            app.next();
+            assert_eq!(app.table_state.selected(), None);
+            app.previous();
+            assert_eq!(app.table_state.selected(), None);
+            assert_eq!(app.selected_symbol(), None);
         }
     }
 }
@@ -0,0 +1,77 @@
+use tui::style::Color;
+
+/// Named colors used throughout the UI, selected with the `theme` config option
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    /// Table headers, key hints and other labels that structure the screen
+    pub header: Color,
+    /// The active sort column and emphasized figures
+    pub highlight: Color,
+    /// Gains
+    pub positive: Color,
+    /// Losses and errors
+    pub negative: Color,
+    /// Warnings such as stale data
+    pub warning: Color,
+    /// Chart lines, bars and popup borders
+    pub accent: Color,
+    /// Regular text and borders
+    pub text: Color,
+    /// Secondary labels, axis ticks and placeholders
+    pub muted: Color,
+    /// Background of alternate table rows when `stripe_rows` is on
+    pub stripe: Color,
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Theme {
+            header: Color::Yellow,
+            highlight: Color::Cyan,
+            positive: Color::Green,
+            negative: Color::Red,
+            warning: Color::LightYellow,
+            accent: Color::Yellow,
+            text: Color::White,
+            muted: Color::DarkGray,
+            stripe: Color::Rgb(0x26, 0x26, 0x26),
+        }
+    }
+
+    pub fn light() -> Self {
+        Theme {
+            header: Color::Blue,
+            highlight: Color::Magenta,
+            positive: Color::Green,
+            negative: Color::Red,
+            warning: Color::Rgb(0xcb, 0x4b, 0x16),
+            accent: Color::Blue,
+            text: Color::Black,
+            muted: Color::Gray,
+            stripe: Color::Rgb(0xee, 0xee, 0xee),
+        }
+    }
+
+    pub fn solarized() -> Self {
+        Theme {
+            header: Color::Rgb(0xb5, 0x89, 0x00),    // yellow
+            highlight: Color::Rgb(0x2a, 0xa1, 0x98), // cyan
+            positive: Color::Rgb(0x85, 0x99, 0x00),  // green
+            negative: Color::Rgb(0xdc, 0x32, 0x2f),  // red
+            warning: Color::Rgb(0xcb, 0x4b, 0x16),   // orange
+            accent: Color::Rgb(0x26, 0x8b, 0xd2),    // blue
+            text: Color::Rgb(0x93, 0xa1, 0xa1),      // base1
+            muted: Color::Rgb(0x58, 0x6e, 0x75),     // base01
+            stripe: Color::Rgb(0x07, 0x36, 0x42),    // base02
+        }
+    }
+
+    /// Resolves a theme by name, falling back to the dark theme for missing or unknown names
+    pub fn from_name(name: Option<&str>) -> Self {
+        match name.map(|n| n.to_lowercase()).as_deref() {
+            Some("light") => Theme::light(),
+            Some("solarized") => Theme::solarized(),
+            _ => Theme::dark(),
+        }
+    }
+}
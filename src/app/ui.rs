@@ -1,19 +1,28 @@
 use tui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout, Alignment},
-    style::{Color, Modifier, Style},
-    widgets::{Block, Borders, Row, Table, Paragraph, Chart, Dataset, Axis, GraphType, Tabs, List, ListItem, Wrap},
+    style::{Modifier, Style},
+    layout::Rect,
+    widgets::{Block, Borders, Row, Table, Paragraph, Chart, Dataset, Axis, GraphType, Tabs, List, ListItem, Wrap, Clear},
     Frame,
     symbols,
     text::{Span, Spans},
 };
 use crossterm::style::Stylize;
+use std::sync::atomic::{AtomicBool, Ordering};
 
-use crate::app::state::{App, SortColumn, InputMode};
-use crate::utils::formatters::{format_volume, format_market_cap, format_price};
+use crate::app::state::{AllocationSort, App, SortColumn, InputMode};
+use crate::app::theme::Theme;
+use crate::models::crypto::Quote;
+use crate::models::portfolio::PortfolioTotals;
+use crate::services::api::Credits;
+use crate::services::cache::ValuePoint;
+use crate::services::logger::log_error;
+use crate::utils::formatters::{format_volume, format_market_cap, format_price, format_amount, format_holdings, format_percent, format_decimal, group_thousands, currency_symbol, sparkline};
 
 pub fn draw<B: Backend>(f: &mut Frame<B>, app: &mut App) {
     let size = f.size();
+    app.compact_active = app.compact_layout.unwrap_or(size.width < COMPACT_WIDTH);
     
     // Calculate dynamic constraints based on terminal height
     let chart_height = if size.height < 20 {
@@ -31,7 +40,7 @@ pub fn draw<B: Backend>(f: &mut Frame<B>, app: &mut App) {
         .direction(Direction::Vertical)
         .margin(1)
         .constraints(
-            if app.input_mode == InputMode::Editing {
+            if matches!(app.input_mode, InputMode::Editing | InputMode::Confirm | InputMode::Filter) {
                 vec![
                     Constraint::Length(3),                    // Tab bar (fixed)
                     Constraint::Percentage(chart_height),     // Chart area (dynamic)
@@ -62,37 +71,326 @@ pub fn draw<B: Backend>(f: &mut Frame<B>, app: &mut App) {
     match app.tab_index {
         0 => draw_watchlist(f, app, chunks[2]),
         1 => draw_portfolio(f, app, chunks[2]),
-        2 => draw_market(f, chunks[2]),
+        2 => draw_market(f, app, chunks[2]),
         _ => unreachable!(),
     }
 
     draw_help(f, app, chunks[3]);
     
-    if app.input_mode == InputMode::Editing {
-        draw_input(f, app, chunks[4]);
+    match app.input_mode {
+        InputMode::Editing => draw_input(f, app, chunks[4]),
+        InputMode::Confirm => draw_confirmation(f, app, chunks[4]),
+        InputMode::Filter => draw_filter_input(f, app, chunks[4]),
+        _ => {}
+    }
+
+    if app.input_mode == InputMode::Detail {
+        draw_detail(f, app, size);
+    }
+
+    if app.show_help {
+        draw_help_overlay(f, app, size);
+    }
+}
+
+/// Colors a percentage change using the configured bands: small moves are muted,
+/// larger ones green/red, and moves past the strong threshold are also bold
+fn change_style(app: &App, value: Option<f64>) -> Style {
+    let theme = app.theme;
+    let thresholds = app.config.change_thresholds;
+    match value {
+        Some(v) if v.abs() < thresholds.neutral => Style::default().fg(theme.muted),
+        Some(v) => {
+            let style = Style::default().fg(if v >= 0.0 { theme.positive } else { theme.negative });
+            if v.abs() >= thresholds.strong {
+                style.add_modifier(Modifier::BOLD)
+            } else {
+                style
+            }
+        }
+        None => Style::default(),
+    }
+}
+
+/// Colors a position's P/L relative to the rest of the portfolio: gains and losses are
+/// dimmed when small compared to the biggest winner (`max_pct`) or loser (`min_pct`),
+/// and the biggest winner and loser themselves are bold
+fn relative_pl_style(theme: Theme, pl_pct: f64, min_pct: f64, max_pct: f64) -> Style {
+    let (color, extreme) = if pl_pct >= 0.0 {
+        (theme.positive, max_pct)
+    } else {
+        (theme.negative, min_pct)
+    };
+    let style = Style::default().fg(color);
+    let strength = if extreme != 0.0 { pl_pct / extreme } else { 0.0 };
+    match strength {
+        s if s >= 1.0 => style.add_modifier(Modifier::BOLD),
+        s if s < 1.0 / 3.0 => style.add_modifier(Modifier::DIM),
+        _ => style,
+    }
+}
+
+/// A token's tags as reversed-color chips followed by its note, for detail lines
+fn annotation_spans(tags: &[String], note: Option<&str>, theme: Theme) -> Spans<'static> {
+    let palette = [theme.accent, theme.highlight, theme.positive, theme.warning, theme.header];
+    let mut spans = Vec::new();
+    for tag in tags {
+        // The same tag always gets the same color
+        let color = palette[tag.to_lowercase().bytes().map(usize::from).sum::<usize>() % palette.len()];
+        spans.push(Span::styled(
+            format!(" {} ", tag),
+            Style::default().fg(color).add_modifier(Modifier::REVERSED),
+        ));
+        spans.push(Span::raw(" "));
+    }
+    if let Some(note) = note {
+        spans.push(Span::styled(
+            note.to_string(),
+            Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
+        ));
+    }
+    Spans::from(spans)
+}
+
+/// Sentiment band for a fear & greed index value, used for the chart's axis labels
+fn fear_greed_label(value: f64) -> &'static str {
+    match value {
+        v if v < 25.0 => "Ex.Fear",
+        v if v < 45.0 => "Fear",
+        v if v <= 55.0 => "Neutral",
+        v if v <= 75.0 => "Greed",
+        _ => "Ex.Greed",
+    }
+}
+
+/// Number of data rows that fit in a bordered table with a one-line header and margin
+fn table_page_size(area: Rect) -> usize {
+    area.height.saturating_sub(4).max(1) as usize
+}
+
+/// Formats a duration as a short age, e.g. "45s", "12m" or "2h 5m"
+fn format_age(age: chrono::Duration) -> String {
+    match age.num_seconds() {
+        s if s < 60 => format!("{}s", s),
+        s if s < 3600 => format!("{}m", s / 60),
+        s => format!("{}h {}m", s / 3600, (s % 3600) / 60),
     }
 }
 
+/// Characters on each side of the center line in the watchlist's 24h change bars
+const CHANGE_BAR_HALF_WIDTH: usize = 8;
+
+/// Draws a diverging bar for a 24h change: losses grow left of the center line in
+/// the negative color, gains grow right in the positive color. `max_abs` fills one half
+fn change_bar(value: Option<f64>, max_abs: f64, theme: Theme) -> Spans<'static> {
+    let v = match value {
+        Some(v) if max_abs > 0.0 => v,
+        _ => return Spans::from(" ".repeat(CHANGE_BAR_HALF_WIDTH * 2 + 1)),
+    };
+    let len = ((v.abs() / max_abs) * CHANGE_BAR_HALF_WIDTH as f64).round() as usize;
+    let len = len.min(CHANGE_BAR_HALF_WIDTH);
+
+    let (left, right) = if v < 0.0 {
+        (
+            Span::styled(
+                format!("{}{}", " ".repeat(CHANGE_BAR_HALF_WIDTH - len), "█".repeat(len)),
+                Style::default().fg(theme.negative),
+            ),
+            Span::raw(" ".repeat(CHANGE_BAR_HALF_WIDTH)),
+        )
+    } else {
+        (
+            Span::raw(" ".repeat(CHANGE_BAR_HALF_WIDTH)),
+            Span::styled(
+                format!("{}{}", "█".repeat(len), " ".repeat(CHANGE_BAR_HALF_WIDTH - len)),
+                Style::default().fg(theme.positive),
+            ),
+        )
+    };
+
+    Spans::from(vec![left, Span::styled("│", Style::default().fg(theme.muted)), right])
+}
+
+const RANGE_WIDTH: usize = 11;
+
+/// Terminals narrower than this get the compact watchlist unless a layout was picked with `w`
+const COMPACT_WIDTH: u16 = 120;
+
+/// Draws where `price` sits between the 24h low (left end) and high (right end)
+fn range_indicator(price: f64, low: Option<f64>, high: Option<f64>, theme: Theme) -> Spans<'static> {
+    let (low, high) = match (low, high) {
+        (Some(low), Some(high)) if high > low => (low, high),
+        _ => return Spans::from("N/A"),
+    };
+    let position = ((price - low) / (high - low)).clamp(0.0, 1.0);
+    let marker = (position * (RANGE_WIDTH - 1) as f64).round() as usize;
+
+    Spans::from(vec![
+        Span::styled("─".repeat(marker), Style::default().fg(theme.muted)),
+        Span::styled("●", Style::default().fg(theme.highlight)),
+        Span::styled("─".repeat(RANGE_WIDTH - 1 - marker), Style::default().fg(theme.muted)),
+    ])
+}
+
+/// Widest an auto-sized watchlist column gets, so one odd value can't squeeze out the rest
+const AUTO_SIZE_MAX_WIDTH: usize = 24;
+
+/// A table cell along with the width of its text, for auto-sized columns
+fn sized_cell(text: String, style: Style) -> (tui::widgets::Cell<'static>, usize) {
+    let width = text.chars().count();
+    (tui::widgets::Cell::from(text).style(style), width)
+}
+
+/// Width of the trend column: one block per point in [`trend_sparkline`]
+const TREND_WIDTH: usize = 6;
+
+/// Share of the plan's API credits below which the remaining count turns the warning color
+const LOW_CREDITS_FRACTION: f64 = 0.1;
+
+/// Sparkline of the prices implied by the 90d/30d/7d/24h/1h changes, oldest first and
+/// ending at the current price. Missing changes are left out, so the line may be shorter
+fn trend_sparkline(quote: &Quote, theme: Theme) -> Span<'static> {
+    let mut prices: Vec<f64> = [
+        quote.percent_change_90d,
+        quote.percent_change_30d,
+        quote.percent_change_7d,
+        quote.percent_change_24h,
+        quote.percent_change_1h,
+    ].iter()
+        .flatten()
+        .filter(|change| **change > -100.0)
+        .map(|change| quote.price / (1.0 + change / 100.0))
+        .collect();
+    prices.push(quote.price);
+
+    if prices.len() < 2 {
+        return Span::raw("N/A");
+    }
+    let color = if prices[prices.len() - 1] >= prices[0] { theme.positive } else { theme.negative };
+    Span::styled(sparkline(&prices), Style::default().fg(color))
+}
+
+/// RSI at or above which a coin counts as overbought, and at or below which as oversold
+const RSI_OVERBOUGHT: f64 = 70.0;
+const RSI_OVERSOLD: f64 = 30.0;
+
+/// RSI cell, red when overbought and green when oversold. "-" until it's fetched or
+/// when the coin has too little history
+fn rsi_cell(rsi: Option<f64>, theme: Theme) -> (tui::widgets::Cell<'static>, usize) {
+    match rsi {
+        Some(rsi) => sized_cell(format_decimal(rsi, 0), Style::default().fg(if rsi >= RSI_OVERBOUGHT {
+            theme.negative
+        } else if rsi <= RSI_OVERSOLD {
+            theme.positive
+        } else {
+            theme.text
+        })),
+        None => sized_cell("-".to_string(), Style::default().fg(theme.muted)),
+    }
+}
+
+/// Header and width in the wide and compact layouts of a sortable watchlist column
+fn watchlist_column(column: SortColumn) -> (&'static str, u16, u16) {
+    match column {
+        SortColumn::Symbol => ("Symbol", 8, 8),
+        SortColumn::Price => ("Price", 14, 11),
+        SortColumn::Change1h => ("Δ 1h %", 10, 9),
+        SortColumn::Change24h => ("Δ 24h %", 10, 9),
+        SortColumn::Change7d => ("Δ 7d %", 10, 9),
+        SortColumn::Change30d => ("Δ 30d %", 10, 9),
+        SortColumn::Change90d => ("Δ 90d %", 10, 9),
+        SortColumn::Volume24h => ("Volume (24h)", 14, 12),
+        SortColumn::VolumeChange => ("Δ 24h %", 12, 12),
+        SortColumn::MarketCap => ("Market Cap", 12, 12),
+        // Portfolio columns never reach the watchlist
+        _ => ("", 10, 10),
+    }
+}
+
+/// Returns the current frame of the loading spinner, advancing every 100ms
+fn spinner_frame() -> &'static str {
+    const FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+    let tick = chrono::Local::now().timestamp_millis() / 100;
+    FRAMES[tick.rem_euclid(FRAMES.len() as i64) as usize]
+}
+
+/// Returns a rectangle of the given percentage size centered inside `area`
+fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(area);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(vertical[1])[1]
+}
+
 fn draw_tabs<B: Backend>(f: &mut Frame<B>, app: &App, area: tui::layout::Rect) {
-    let titles = vec!["Watchlist", "Portfolio", "Market"]
+    let theme = app.theme;
+    let titles = ["Watchlist", "Portfolio", "Market"]
         .iter()
         .map(|t| Spans::from(Span::styled(
             *t,
-            Style::default().fg(Color::White)
+            Style::default().fg(theme.text)
         )))
         .collect();
 
+    let mut block = Block::default().borders(Borders::ALL);
+    if app.paused {
+        block = block.title(Span::styled(
+            " PAUSED — p to resume ",
+            Style::default().fg(theme.warning).add_modifier(Modifier::BOLD),
+        ));
+    }
     let tabs = Tabs::new(titles)
-        .block(Block::default().borders(Borders::ALL))
+        .block(block)
         .highlight_style(Style::default()
-            .fg(Color::Yellow)
+            .fg(theme.header)
             .add_modifier(Modifier::BOLD))
         .select(app.tab_index);
 
     f.render_widget(tabs, area);
 }
 
+/// Logs the first unparseable fear & greed timestamp; the chart is redrawn constantly,
+/// so later ones would only flood the log
+fn warn_bad_fear_greed_timestamp(timestamp: &str) {
+    static WARNED: AtomicBool = AtomicBool::new(false);
+    if !WARNED.swap(true, Ordering::Relaxed) {
+        log_error("Fear & Greed", &format!(
+            "Invalid timestamp '{}' in fear & greed data, showing '?' on the chart axis", timestamp)).unwrap_or(());
+    }
+}
+
 fn draw_fear_greed_chart<B: Backend>(f: &mut Frame<B>, app: &App, area: tui::layout::Rect) {
+    let theme = app.theme;
+    if app.fear_greed_data.is_empty() {
+        let (message, color) = match &app.fear_greed_error {
+            Some(error) => (
+                format!("Fear & Greed unavailable: {}\nPress f to retry", error),
+                theme.warning,
+            ),
+            None => ("Loading Fear & Greed data...".to_string(), theme.muted),
+        };
+        let placeholder = Paragraph::new(message)
+            .block(Block::default().title("Fear & Greed Index").borders(Borders::ALL))
+            .style(Style::default().fg(color))
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true });
+        f.render_widget(placeholder, area);
+        return;
+    }
+
     let fear_greed_points: Vec<(f64, f64)> = app.fear_greed_data.iter()
         .rev()  // Reverse to get oldest first
         .enumerate()
@@ -114,6 +412,10 @@ fn draw_fear_greed_chart<B: Backend>(f: &mut Frame<B>, app: &App, area: tui::lay
         std::cmp::Ordering::Equal => "→",
     };
 
+    // Daily points, newest first: the value `days` entries back is that many days ago
+    let delta = |days: usize| app.fear_greed_data.get(days)
+        .map_or("N/A".to_string(), |past| format!("{:+}", current_value as i64 - past.value as i64));
+
     let values: Vec<u64> = app.fear_greed_data.iter()
         .map(|fg| fg.value)
         .collect();
@@ -121,14 +423,43 @@ fn draw_fear_greed_chart<B: Backend>(f: &mut Frame<B>, app: &App, area: tui::lay
     let min_value = values.iter().min().copied().unwrap_or(0);
     let max_value = values.iter().max().copied().unwrap_or(0);
 
-    let datasets = vec![
+    // Fit the y axis to the data, with some padding, within the 0-100 index range
+    let padding = 5.0;
+    let y_min = (min_value as f64 - padding).max(0.0);
+    let y_max = (max_value as f64 + padding).min(100.0).max(y_min + 1.0);
+
+    // Rolling mean, plotted from the first point with a full window behind it
+    let window = app.config.fear_greed_ma_window;
+    let moving_average: Vec<(f64, f64)> = if window > 1 && fear_greed_points.len() >= window {
+        fear_greed_points.windows(window)
+            .map(|points| {
+                let x = points[window - 1].0;
+                (x, points.iter().map(|(_, y)| y).sum::<f64>() / window as f64)
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let ma_name = format!("{}-pt MA", window);
+
+    let mut datasets = vec![
         Dataset::default()
             .name("Fear & Greed")
             .marker(symbols::Marker::Braille)
             .graph_type(GraphType::Line)
-            .style(Style::default().fg(Color::Yellow))
+            .style(Style::default().fg(theme.accent))
             .data(&fear_greed_points),
     ];
+    if !moving_average.is_empty() {
+        datasets.push(
+            Dataset::default()
+                .name(ma_name.as_str())
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(theme.highlight))
+                .data(&moving_average),
+        );
+    }
 
     let unknown_str = "Unknown".to_string();
     let current_classification = app.fear_greed_data.first()
@@ -136,10 +467,12 @@ fn draw_fear_greed_chart<B: Backend>(f: &mut Frame<B>, app: &App, area: tui::lay
         .unwrap_or(&unknown_str);
     
     let title = format!(
-        "Fear & Greed Index: {} {} ({}) | Min: {} | Max: {}", 
+        "Fear & Greed Index: {} {} ({}) | 7d: {}, 30d: {} | Min: {} | Max: {}",
         current_value,
         trend,
         current_classification,
+        delta(7),
+        delta(30),
         min_value,
         max_value,
     );
@@ -149,7 +482,7 @@ fn draw_fear_greed_chart<B: Backend>(f: &mut Frame<B>, app: &App, area: tui::lay
             .title(title)
             .borders(Borders::ALL))
         .x_axis(Axis::default()
-            .style(Style::default().fg(Color::White))
+            .style(Style::default().fg(theme.text))
             .bounds([0.0, fear_greed_points.len() as f64])
             .labels(app.fear_greed_data.iter()
                 .rev()
@@ -161,134 +494,211 @@ fn draw_fear_greed_chart<B: Backend>(f: &mut Frame<B>, app: &App, area: tui::lay
                     i % 7 == 0 // Show regular intervals
                 })
                 .map(|(_, fg)| {
-                    let ts = fg.timestamp.parse::<i64>().unwrap_or(0);
-                    let date = chrono::DateTime::from_timestamp(ts, 0)
-                        .unwrap_or_default()
-                        .format("%b %-d")
-                        .to_string();
+                    let date = match fg.date() {
+                        Some(date) => date.format("%b %-d").to_string(),
+                        None => {
+                            warn_bad_fear_greed_timestamp(&fg.timestamp);
+                            "?".to_string()
+                        }
+                    };
                     Span::styled(
                         date,
-                        Style::default().fg(Color::Gray)
+                        Style::default().fg(theme.muted)
                     )
                 })
                 .collect()))
         .y_axis(Axis::default()
-            .style(Style::default().fg(Color::White))
-            .bounds([25.0, 100.0])
-            .labels(vec![
-                "25 Fear",
-                "50 Neutral",
-                "75 Greed",
-                "100 Ex.Greed"
-            ]
-                .into_iter()
-                .map(Span::from)
+            .style(Style::default().fg(theme.text))
+            .bounds([y_min, y_max])
+            .labels((0..=3)
+                .map(|i| {
+                    let value = y_min + (y_max - y_min) * i as f64 / 3.0;
+                    Span::from(format!("{:.0} {}", value, fear_greed_label(value)))
+                })
                 .collect()));
 
     f.render_widget(chart, area);
 }
 
 fn draw_watchlist<B: Backend>(f: &mut Frame<B>, app: &mut App, area: tui::layout::Rect) {
-    let header_cells = [
-        ("Symbol", SortColumn::Symbol),
-        ("Price", SortColumn::Price),
-        ("Δ 1h %", SortColumn::Change1h),
-        ("Δ 24h %", SortColumn::Change24h),
-        ("Δ 7d %", SortColumn::Change7d),
-        ("Δ 30d %", SortColumn::Change30d),
-        ("Δ 90d %", SortColumn::Change90d),
-        ("Volume (24h)", SortColumn::Volume24h),
-        ("Δ 24h %", SortColumn::VolumeChange),
-        ("Market Cap", SortColumn::MarketCap),
-    ]
-    .iter()
-    .map(|(h, col)| {
-        let mut text = (*h).to_string();
-        if *col == app.sort_column {
-            text = format!("{} {}", text, if app.sort_ascending { "↑" } else { "↓" });
-        }
-        tui::widgets::Cell::from(text).style(
-            Style::default()
-                .fg(if *col == app.sort_column { Color::Cyan } else { Color::Yellow })
-                .add_modifier(Modifier::BOLD),
-        )
-    });
-
-    let mut sorted_cryptos: Vec<_> = app.crypto_data.values()
-        .filter(|crypto| {
-            app.config.tokens.iter().any(|token| {
-                let config_name = token.name.to_lowercase()
-                    .replace("-", " ")
-                    .replace("_", " ");
-                let crypto_name = crypto.name.to_lowercase()
-                    .replace("-", " ")
-                    .replace("_", " ");
-                token.is_in_watchlist() && config_name == crypto_name
-            })
+    let theme = app.theme;
+    let wide = !app.compact_active;
+    // The configured sortable columns the layout has room for
+    let columns = app.watchlist_columns().into_iter()
+        .filter(|col| app.is_column_visible(*col))
+        .map(|col| {
+            let (header, width, compact_width) = watchlist_column(col);
+            (header, col, width, compact_width)
         })
-        .collect();
+        .collect::<Vec<_>>();
 
-    sorted_cryptos.sort_by(|a, b| {
-        let quote_a = a.quote.get("USD").unwrap();
-        let quote_b = b.quote.get("USD").unwrap();
-        let cmp = match app.sort_column {
-            SortColumn::Symbol => a.symbol.cmp(&b.symbol),
-            SortColumn::Price => quote_a.price.partial_cmp(&quote_b.price).unwrap_or(std::cmp::Ordering::Equal),
-            SortColumn::Change1h => quote_a.percent_change_1h.partial_cmp(&quote_b.percent_change_1h).unwrap_or(std::cmp::Ordering::Equal),
-            SortColumn::Change24h => quote_a.percent_change_24h.partial_cmp(&quote_b.percent_change_24h).unwrap_or(std::cmp::Ordering::Equal),
-            SortColumn::Change7d => quote_a.percent_change_7d.partial_cmp(&quote_b.percent_change_7d).unwrap_or(std::cmp::Ordering::Equal),
-            SortColumn::Change30d => quote_a.percent_change_30d.partial_cmp(&quote_b.percent_change_30d).unwrap_or(std::cmp::Ordering::Equal),
-            SortColumn::Change90d => quote_a.percent_change_90d.partial_cmp(&quote_b.percent_change_90d).unwrap_or(std::cmp::Ordering::Equal),
-            SortColumn::Volume24h => quote_a.volume_24h.partial_cmp(&quote_b.volume_24h).unwrap_or(std::cmp::Ordering::Equal),
-            SortColumn::VolumeChange => quote_a.volume_change_24h.partial_cmp(&quote_b.volume_change_24h).unwrap_or(std::cmp::Ordering::Equal),
-            SortColumn::MarketCap => quote_a.market_cap.partial_cmp(&quote_b.market_cap).unwrap_or(std::cmp::Ordering::Equal),
-            _ => std::cmp::Ordering::Equal, // Handle portfolio-specific columns
-        };
-        if app.sort_ascending { cmp } else { cmp.reverse() }
-    });
+    let mut header_cells = columns.iter()
+        .map(|(h, col, _, _)| {
+            let mut text = (*h).to_string();
+            if *col == app.sort_column {
+                text = format!("{} {}", text, if app.watchlist_sort_ascending { "↑" } else { "↓" });
+            }
+            sized_cell(text, Style::default()
+                .fg(if *col == app.sort_column { theme.highlight } else { theme.header })
+                .add_modifier(Modifier::BOLD))
+        })
+        .collect::<Vec<_>>();
+
+    if wide {
+        for label in ["24h Low", "24h High", "24h Range", "Trend"] {
+            header_cells.push(sized_cell(
+                label.to_string(),
+                Style::default().fg(theme.header).add_modifier(Modifier::BOLD),
+            ));
+        }
+    }
 
-    let rows = sorted_cryptos.iter().enumerate().map(|(i, crypto)| {
-        let quote = crypto.quote.get("USD").unwrap_or_else(|| {
-            panic!("USD quote not found for {}", crypto.symbol)
+    // Change since the previous session ended, once there's a snapshot to compare with
+    let since_label = app.last_viewed.as_ref()
+        .filter(|_| wide)
+        .and_then(|snapshot| snapshot.local_time())
+        .map(|viewed_at| if viewed_at.date_naive() == chrono::Local::now().date_naive() {
+            format!("Δ Since {}", viewed_at.format("%H:%M"))
+        } else {
+            format!("Δ Since {}", viewed_at.format("%b %d"))
         });
+    if let Some(label) = &since_label {
+        header_cells.push(sized_cell(
+            label.clone(),
+            Style::default().fg(theme.header).add_modifier(Modifier::BOLD),
+        ));
+    }
+
+    let show_rsi = app.config.show_rsi && wide;
+    if show_rsi {
+        header_cells.push(sized_cell(
+            "RSI 14".to_string(),
+            Style::default().fg(theme.header).add_modifier(Modifier::BOLD),
+        ));
+    }
+
+    let show_bars = app.config.show_change_bars && wide;
+    if show_bars {
+        header_cells.push(sized_cell(
+            "24h".to_string(),
+            Style::default().fg(theme.header).add_modifier(Modifier::BOLD),
+        ));
+    }
+
+    let sorted_cryptos = app.watchlist_rows();
+    // Bars are scaled to the largest 24h move among the visible rows
+    let max_abs_change = sorted_cryptos.iter()
+        .filter_map(|crypto| app.quote(crypto).and_then(|q| q.percent_change_24h))
+        .fold(0.0_f64, |max, v| max.max(v.abs()));
 
+    let currency = app.config.convert_currency.as_str();
+    let row_cells = sorted_cryptos.iter().map(|crypto| {
         // Style helpers for percentage changes
-        let style_change = |value: Option<f64>| {
-            match value {
-                Some(v) if v >= 0.0 => Style::default().fg(Color::Green),
-                Some(_) => Style::default().fg(Color::Red),
-                None => Style::default(),
-            }
-        };
+        let style_change = |value: Option<f64>| change_style(app, value);
+        let text = |text: String| sized_cell(text, Style::default());
 
-        let mut row = Row::new(vec![
-            tui::widgets::Cell::from(crypto.symbol.clone()),
-            tui::widgets::Cell::from(format_price(quote.price)),
-            tui::widgets::Cell::from(quote.percent_change_1h.map_or("N/A".to_string(), |v| format!("{:+.2}%", v)))
-                .style(style_change(quote.percent_change_1h)),
-            tui::widgets::Cell::from(quote.percent_change_24h.map_or("N/A".to_string(), |v| format!("{:+.2}%", v)))
-                .style(style_change(quote.percent_change_24h)),
-            tui::widgets::Cell::from(quote.percent_change_7d.map_or("N/A".to_string(), |v| format!("{:+.2}%", v)))
-                .style(style_change(quote.percent_change_7d)),
-            tui::widgets::Cell::from(quote.percent_change_30d.map_or("N/A".to_string(), |v| format!("{:+.2}%", v)))
-                .style(style_change(quote.percent_change_30d)),
-            tui::widgets::Cell::from(quote.percent_change_90d.map_or("N/A".to_string(), |v| format!("{:+.2}%", v)))
-                .style(style_change(quote.percent_change_90d)),
-            tui::widgets::Cell::from(format_volume(quote.volume_24h)),
-            tui::widgets::Cell::from(quote.volume_change_24h.map_or("N/A".to_string(), |v| format!("{:+.2}%", v)))
-                .style(style_change(quote.volume_change_24h)),
-            tui::widgets::Cell::from(format_market_cap(quote.market_cap)),
-        ]);
+        let symbol = if app.is_pinned(crypto) {
+            format!("★ {}", crypto.symbol)
+        } else {
+            crypto.symbol.clone()
+        };
+        let percent = |value: Option<f64>| {
+            sized_cell(format_percent(value, app.config.percent_precision), style_change(value))
+        };
 
-        // Highlight the selected row
-        if let Some(selected) = app.table_state.selected() {
-            if selected == i {
-                row = row.style(Style::default().add_modifier(Modifier::REVERSED));
+        let quote = app.quote(crypto);
+        let mut cells = columns.iter()
+            .map(|(_, col, _, _)| match (col, quote) {
+                (SortColumn::Symbol, _) => text(symbol.clone()),
+                // No quote in the configured currency: keep the row but leave the figures blank
+                (_, None) => text("N/A".to_string()),
+                (SortColumn::Price, Some(quote)) => text(format_price(quote.price, currency)),
+                (SortColumn::Change1h, Some(quote)) => percent(quote.percent_change_1h),
+                (SortColumn::Change24h, Some(quote)) => percent(quote.percent_change_24h),
+                (SortColumn::Change7d, Some(quote)) => percent(quote.percent_change_7d),
+                (SortColumn::Change30d, Some(quote)) => percent(quote.percent_change_30d),
+                (SortColumn::Change90d, Some(quote)) => percent(quote.percent_change_90d),
+                (SortColumn::Volume24h, Some(quote)) => text(format_volume(quote.volume_24h, currency)),
+                (SortColumn::VolumeChange, Some(quote)) => percent(quote.volume_change_24h),
+                (SortColumn::MarketCap, Some(quote)) => text(format_market_cap(quote.market_cap, currency)),
+                (_, Some(_)) => text(String::new()),
+            })
+            .collect::<Vec<_>>();
+        // The 24h low/high/range and trend columns after the sortable ones only fit the wide layout
+        if wide {
+            match quote {
+                Some(quote) => cells.extend([
+                    text(quote.low_24h.map_or("N/A".to_string(), |v| format_price(v, currency))),
+                    text(quote.high_24h.map_or("N/A".to_string(), |v| format_price(v, currency))),
+                    (tui::widgets::Cell::from(range_indicator(quote.price, quote.low_24h, quote.high_24h, theme)), RANGE_WIDTH),
+                    (tui::widgets::Cell::from(trend_sparkline(quote, theme)), TREND_WIDTH),
+                ]),
+                None => cells.extend((0..4).map(|_| text("N/A".to_string()))),
+            }
+        }
+        if since_label.is_some() {
+            let change = app.change_since_last_viewed(crypto);
+            cells.push(sized_cell(format_percent(change, app.config.percent_precision), change_style(app, change)));
+        }
+        if show_rsi {
+            cells.push(rsi_cell(app.rsi.get(&crypto.symbol).copied().flatten(), theme));
+        }
+        if show_bars {
+            let change = app.quote(crypto).and_then(|q| q.percent_change_24h);
+            cells.push((tui::widgets::Cell::from(change_bar(change, max_abs_change, theme)), CHANGE_BAR_HALF_WIDTH * 2 + 1));
+        }
+        cells
+    }).collect::<Vec<_>>();
+
+    // Auto-sized columns fit the widest header or visible cell, up to a cap
+    let content_widths = (0..header_cells.len())
+        .map(|col| {
+            row_cells.iter()
+                .filter_map(|cells| cells.get(col).map(|(_, width)| *width))
+                .chain(std::iter::once(header_cells[col].1))
+                .max()
+                .unwrap_or(0)
+                .min(AUTO_SIZE_MAX_WIDTH) as u16
+        })
+        .collect::<Vec<_>>();
+    let header_cells = header_cells.into_iter().map(|(cell, _)| cell).collect::<Vec<_>>();
+
+    // With grouping on, a header row starts each section
+    let grouped = app.config.group_watchlist;
+    let group_label = |group: Option<&str>| format!("▾ {}", group.unwrap_or("Other"));
+    let mut rows = Vec::with_capacity(row_cells.len());
+    let mut selected_row = None;
+    let mut current_group = None;
+    for (i, (crypto, cells)) in sorted_cryptos.iter().zip(row_cells).enumerate() {
+        if grouped {
+            let group = app.watchlist_group(crypto);
+            if current_group != Some(group) {
+                rows.push(Row::new(vec![tui::widgets::Cell::from(group_label(group)).style(
+                    Style::default().fg(theme.header).add_modifier(Modifier::BOLD),
+                )]));
+                current_group = Some(group);
             }
         }
 
-        row
-    });
+        let mut row = Row::new(cells.into_iter().map(|(cell, _)| cell));
+        if app.config.stripe_rows && i % 2 == 0 {
+            row = row.style(Style::default().bg(theme.stripe));
+        }
+
+        // Highlight the selected row, replacing any stripe
+        if app.table_state.selected() == Some(i) {
+            row = row.style(Style::default().add_modifier(Modifier::REVERSED));
+            selected_row = Some(rows.len());
+        }
+
+        rows.push(row);
+    }
+    let group_label_width = sorted_cryptos.iter()
+        .filter(|_| grouped)
+        .map(|crypto| group_label(app.watchlist_group(crypto)).chars().count())
+        .max()
+        .unwrap_or(0)
+        .min(AUTO_SIZE_MAX_WIDTH) as u16;
 
     let header = Row::new(header_cells).height(1).bottom_margin(1);
 
@@ -301,109 +711,122 @@ fn draw_watchlist<B: Backend>(f: &mut Frame<B>, app: &mut App, area: tui::layout
             "Crypto Prices (Error: {})",
             error
         ).red().to_string(),
-        (None, None) => "Crypto Prices (Not Updated Yet)".to_string(),
+        (None, None) => match &app.cached_at {
+            Some(time) => format!(
+                "Crypto Prices (Cached from {}, refreshing...)",
+                time.format("%Y-%m-%d %H:%M:%S")
+            ),
+            None => "Crypto Prices (Not Updated Yet)".to_string(),
+        },
+    };
+    let (title, title_style) = match app.stale_age() {
+        Some(age) => (
+            format!("STALE — last updated {} ago | {}", format_age(age), title),
+            Style::default().fg(theme.warning).add_modifier(Modifier::BOLD),
+        ),
+        None => (title, Style::default()),
+    };
+    let title = match app.filter.as_deref() {
+        Some(filter) if !filter.is_empty() => format!("{} [Filter: {}]", title, filter),
+        _ => title,
+    };
+    let title = match app.seconds_until_refresh() {
+        _ if app.is_loading => format!("{} {} updating…", title, spinner_frame()),
+        _ if app.paused => format!("{} · paused", title),
+        Some(secs) if secs > 0 => format!("{} · next update in {}s", title, secs),
+        Some(_) => format!("{} · updating…", title),
+        None => title,
     };
 
+    let mut widths = columns.iter()
+        .map(|(_, _, width, compact_width)| Constraint::Length(if wide { *width } else { *compact_width }))
+        .collect::<Vec<_>>();
+    if wide {
+        widths.extend([
+            Constraint::Length(14),  // 24h Low
+            Constraint::Length(14),  // 24h High
+            Constraint::Length(RANGE_WIDTH as u16),  // 24h Range
+            Constraint::Length(TREND_WIDTH as u16),  // Trend
+        ]);
+    }
+    if since_label.is_some() {
+        widths.push(Constraint::Length(14));  // Δ Since
+    }
+    if show_rsi {
+        widths.push(Constraint::Length(6));  // RSI
+    }
+    if show_bars {
+        widths.push(Constraint::Length(CHANGE_BAR_HALF_WIDTH as u16 * 2 + 1));  // 24h bar
+    }
+    if app.config.auto_size_columns {
+        widths = content_widths.into_iter().map(Constraint::Length).collect();
+    }
+    // Section labels live in the symbol column
+    if let Some(Constraint::Length(width)) = widths.first_mut() {
+        *width = (*width).max(group_label_width);
+    }
+
     let table = Table::new(rows)
         .header(header)
         .block(Block::default()
             .borders(Borders::ALL)
-            .title(title))
-        .widths(&[
-            Constraint::Length(8),   // Symbol
-            Constraint::Length(14),  // Price
-            Constraint::Length(10),  // 1h %
-            Constraint::Length(10),  // 24h %
-            Constraint::Length(10),  // 7d %
-            Constraint::Length(10),  // 30d %
-            Constraint::Length(10),  // 90d %
-            Constraint::Length(14),  // Volume
-            Constraint::Length(12),  // Volume Change
-            Constraint::Length(12),  // Market Cap
-        ])
+            .title(Span::styled(title, title_style)))
+        .widths(&widths)
         .column_spacing(1);
 
-    f.render_stateful_widget(table, area, &mut app.table_state);
+    app.page_size = table_page_size(area);
+    if grouped {
+        app.grouped_table_state.select(selected_row);
+        f.render_stateful_widget(table, area, &mut app.grouped_table_state);
+    } else {
+        f.render_stateful_widget(table, area, &mut app.table_state);
+    }
+}
+
+/// How to get started, shown in place of the portfolio table while it has no tokens
+fn empty_portfolio_hint(app: &App) -> &'static str {
+    if app.paper_mode {
+        "No paper positions yet — use `buy <coin> <quantity> <price>`"
+    } else {
+        "No portfolio holdings yet — use `add <coin> -p <amount> <price>`"
+    }
+}
+
+/// A bordered block with a single muted, centered line of text
+fn placeholder(text: &'static str, title: &'static str, theme: Theme) -> Paragraph<'static> {
+    Paragraph::new(vec![
+        Spans::from(""),
+        Spans::from(Span::styled(text, Style::default().fg(theme.muted))),
+    ])
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true })
+        .block(Block::default().title(title).borders(Borders::ALL))
 }
 
 fn draw_portfolio<B: Backend>(f: &mut Frame<B>, app: &mut App, area: tui::layout::Rect) {
+    if !app.has_portfolio_tokens() {
+        f.render_widget(placeholder(empty_portfolio_hint(app), "Portfolio", app.theme), area);
+        return;
+    }
+
+    let theme = app.theme;
+    // Calculate portfolio data
+    let positions = app.portfolio_positions();
+    let totals = PortfolioTotals::from_positions(&positions);
+    let annotation = app.table_state.selected()
+        .and_then(|i| positions.get(i))
+        .filter(|position| !position.tags.is_empty() || position.note.is_some())
+        .map(|position| annotation_spans(position.tags, position.note, theme));
+
     // Create layout for the portfolio view
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Min(10),     // Portfolio Table
+            Constraint::Length(if annotation.is_some() { 1 } else { 0 }),  // Tags and note of the selection
         ])
         .split(area);
 
-    // Calculate portfolio data
-    let mut owned_tokens: Vec<_> = app.config.tokens.iter()
-        .filter(|token| token.is_in_portfolio())
-        .filter_map(|token| {
-            app.crypto_data.values()
-                .find(|crypto| {
-                    let config_name = token.name.to_lowercase()
-                        .replace("-", " ")
-                        .replace("_", " ");
-                    let crypto_name = crypto.name.to_lowercase()
-                        .replace("-", " ")
-                        .replace("_", " ");
-                    config_name == crypto_name
-                })
-                .map(|crypto| (token, crypto))
-        })
-        .collect();
-
-    // Sort the portfolio data
-    owned_tokens.sort_by(|(token_a, crypto_a), (token_b, crypto_b)| {
-        let quote_a = crypto_a.quote.get("USD").unwrap();
-        let quote_b = crypto_b.quote.get("USD").unwrap();
-        let holdings_a = token_a.owned.unwrap_or(0.0);
-        let holdings_b = token_b.owned.unwrap_or(0.0);
-        let avg_buy_a = token_a.avg_buy_price.unwrap_or(0.0);
-        let avg_buy_b = token_b.avg_buy_price.unwrap_or(0.0);
-        let current_value_a = holdings_a * quote_a.price;
-        let current_value_b = holdings_b * quote_b.price;
-        let cost_basis_a = holdings_a * avg_buy_a;
-        let cost_basis_b = holdings_b * avg_buy_b;
-        let profit_loss_a = current_value_a - cost_basis_a;
-        let profit_loss_b = current_value_b - cost_basis_b;
-        let profit_loss_pct_a = if cost_basis_a > 0.0 { (profit_loss_a / cost_basis_a) * 100.0 } else { 0.0 };
-        let profit_loss_pct_b = if cost_basis_b > 0.0 { (profit_loss_b / cost_basis_b) * 100.0 } else { 0.0 };
-
-        let cmp = match app.portfolio_sort_column {
-            SortColumn::Symbol => crypto_a.symbol.cmp(&crypto_b.symbol),
-            SortColumn::Price => quote_a.price.partial_cmp(&quote_b.price).unwrap_or(std::cmp::Ordering::Equal),
-            SortColumn::Holdings => holdings_a.partial_cmp(&holdings_b).unwrap_or(std::cmp::Ordering::Equal),
-            SortColumn::AvgBuy => avg_buy_a.partial_cmp(&avg_buy_b).unwrap_or(std::cmp::Ordering::Equal),
-            SortColumn::CurrentValue => current_value_a.partial_cmp(&current_value_b).unwrap_or(std::cmp::Ordering::Equal),
-            SortColumn::CostBasis => cost_basis_a.partial_cmp(&cost_basis_b).unwrap_or(std::cmp::Ordering::Equal),
-            SortColumn::ProfitLoss => profit_loss_a.partial_cmp(&profit_loss_b).unwrap_or(std::cmp::Ordering::Equal),
-            SortColumn::ProfitLossPercent => profit_loss_pct_a.partial_cmp(&profit_loss_pct_b).unwrap_or(std::cmp::Ordering::Equal),
-            SortColumn::Change24h => quote_a.percent_change_24h.partial_cmp(&quote_b.percent_change_24h).unwrap_or(std::cmp::Ordering::Equal),
-            _ => std::cmp::Ordering::Equal,
-        };
-        if app.sort_ascending { cmp } else { cmp.reverse() }
-    });
-
-    let total_value: f64 = owned_tokens.iter()
-        .map(|(token_config, crypto)| {
-            token_config.owned.unwrap_or(0.0) * crypto.quote.get("USD").unwrap().price
-        })
-        .sum();
-
-    let total_cost: f64 = owned_tokens.iter()
-        .map(|(token_config, _)| {
-            token_config.owned.unwrap_or(0.0) * token_config.avg_buy_price.unwrap_or(0.0)
-        })
-        .sum();
-
-    let total_pl = total_value - total_cost;
-    let total_pl_pct = if total_cost > 0.0 {
-        (total_pl / total_cost) * 100.0
-    } else {
-        0.0
-    };
-
     // Portfolio Table
     let header_cells = [
         ("Symbol", SortColumn::Symbol),
@@ -411,66 +834,87 @@ fn draw_portfolio<B: Backend>(f: &mut Frame<B>, app: &mut App, area: tui::layout
         ("Holdings", SortColumn::Holdings),
         ("Avg Buy", SortColumn::AvgBuy),
         ("Current Value", SortColumn::CurrentValue),
+        ("Alloc %", SortColumn::Allocation),
         ("Cost Basis", SortColumn::CostBasis),
         ("P/L", SortColumn::ProfitLoss),
         ("P/L %", SortColumn::ProfitLossPercent),
+        ("Break-even", SortColumn::BreakEven),
+        ("To B/E %", SortColumn::ToBreakEven),
         ("24h Change", SortColumn::Change24h),
+        ("Est. Yield/yr", SortColumn::AnnualYield),
     ].iter().map(|(h, col)| {
         let mut text = (*h).to_string();
         if *col == app.portfolio_sort_column {
-            text = format!("{} {}", text, if app.sort_ascending { "↑" } else { "↓" });
+            text = format!("{} {}", text, if app.portfolio_sort_ascending { "↑" } else { "↓" });
         }
         tui::widgets::Cell::from(text).style(
             Style::default()
-                .fg(if *col == app.portfolio_sort_column { Color::Cyan } else { Color::Yellow })
+                .fg(if *col == app.portfolio_sort_column { theme.highlight } else { theme.header })
                 .add_modifier(Modifier::BOLD),
         )
     });
 
-    let rows = owned_tokens.iter().enumerate().map(|(i, (token_config, crypto))| {
-        let quote = crypto.quote.get("USD").unwrap();
-        let holdings = token_config.owned.unwrap_or(0.0);
-        let avg_buy = token_config.avg_buy_price.unwrap_or(0.0);
-        let current_value = holdings * quote.price;
-        let cost_basis = holdings * avg_buy;
-        let profit_loss = current_value - cost_basis;
-        let profit_loss_pct = if cost_basis > 0.0 {
-            (profit_loss / cost_basis) * 100.0
-        } else {
-            0.0
+    // P/L is colored relative to the best and worst performing holdings
+    let held_pl_pcts = positions.iter()
+        .filter(|position| position.quote.is_some() && position.holdings > 0.0)
+        .map(|position| position.profit_loss_pct);
+    let min_pl_pct = held_pl_pcts.clone().fold(0.0_f64, f64::min);
+    let max_pl_pct = held_pl_pcts.fold(0.0_f64, f64::max);
+
+    let currency = app.config.convert_currency.as_str();
+    let rows = positions.iter().enumerate().map(|(i, position)| {
+        let pl_style = relative_pl_style(theme, position.profit_loss_pct, min_pl_pct, max_pl_pct);
+
+        let mut row = match position.quote {
+            Some(quote) => Row::new(vec![
+                tui::widgets::Cell::from(position.crypto.symbol.clone()),
+                tui::widgets::Cell::from(format_price(quote.price, currency)),
+                tui::widgets::Cell::from(format_holdings(position.holdings, app.config.holdings_precision)),
+                tui::widgets::Cell::from(format_price(position.avg_buy_price, currency)),
+                tui::widgets::Cell::from(format_price(position.current_value, currency)),
+                tui::widgets::Cell::from(
+                    totals.allocation_pct(position)
+                        .map_or("N/A".to_string(), |v| format!("{}%", format_decimal(v, app.config.percent_precision)))
+                ),
+                tui::widgets::Cell::from(format_price(position.cost_basis, currency)),
+                tui::widgets::Cell::from(format_price(position.profit_loss, currency)).style(pl_style),
+                tui::widgets::Cell::from(format_percent(Some(position.profit_loss_pct), app.config.percent_precision)).style(pl_style),
+                tui::widgets::Cell::from(format_price(position.break_even_price, currency)),
+                tui::widgets::Cell::from(
+                    format_percent(position.to_break_even_pct, app.config.percent_precision)
+                ),
+                tui::widgets::Cell::from(
+                    format_percent(quote.percent_change_24h, app.config.percent_precision)
+                ).style(change_style(app, quote.percent_change_24h)),
+                tui::widgets::Cell::from(position.annual_yield.map_or("-".to_string(), |v| format_amount(v, currency))),
+            ]),
+            // No quote in the configured currency: only the holding itself is known
+            None => Row::new(vec![
+                tui::widgets::Cell::from(position.crypto.symbol.clone()),
+                tui::widgets::Cell::from("N/A"),
+                tui::widgets::Cell::from(format_holdings(position.holdings, app.config.holdings_precision)),
+                tui::widgets::Cell::from(format_price(position.avg_buy_price, currency)),
+                tui::widgets::Cell::from("N/A"),
+                tui::widgets::Cell::from("N/A"),
+                tui::widgets::Cell::from(format_price(position.cost_basis, currency)),
+                tui::widgets::Cell::from("N/A"),
+                tui::widgets::Cell::from("N/A"),
+                tui::widgets::Cell::from(format_price(position.break_even_price, currency)),
+                tui::widgets::Cell::from("N/A"),
+                tui::widgets::Cell::from("N/A"),
+                tui::widgets::Cell::from("N/A"),
+            ]),
         };
-
-        let pl_style = if profit_loss >= 0.0 {
-            Style::default().fg(Color::Green)
-        } else {
-            Style::default().fg(Color::Red)
+        let mut row_style = match position.quote {
+            Some(_) => Style::default(),
+            None => Style::default().fg(theme.muted),
         };
+        if app.config.stripe_rows && i % 2 == 0 {
+            row_style = row_style.bg(theme.stripe);
+        }
+        row = row.style(row_style);
 
-        let mut row = Row::new(vec![
-            tui::widgets::Cell::from(crypto.symbol.clone()),
-            tui::widgets::Cell::from(format_price(quote.price)),
-            tui::widgets::Cell::from(format!("{:.4}", holdings)),
-            tui::widgets::Cell::from(format_price(avg_buy)),
-            tui::widgets::Cell::from(format_price(current_value)),
-            tui::widgets::Cell::from(format_price(cost_basis)),
-            tui::widgets::Cell::from(format_price(profit_loss)).style(pl_style),
-            tui::widgets::Cell::from(format!("{:+.2}%", profit_loss_pct)).style(pl_style),
-            tui::widgets::Cell::from(
-                quote.percent_change_24h
-                    .map_or("N/A".to_string(), |v| format!("{:+.2}%", v))
-            ).style(
-                quote.percent_change_24h.map_or(
-                    Style::default(),
-                    |v| if v >= 0.0 {
-                        Style::default().fg(Color::Green)
-                    } else {
-                        Style::default().fg(Color::Red)
-                    }
-                )
-            ),
-        ]);
-
-        // Highlight the selected row
+        // Highlight the selected row, replacing any stripe
         if let Some(selected) = app.table_state.selected() {
             if selected == i {
                 row = row.style(Style::default().add_modifier(Modifier::REVERSED));
@@ -483,9 +927,30 @@ fn draw_portfolio<B: Backend>(f: &mut Frame<B>, app: &mut App, area: tui::layout
     let header = Row::new(header_cells).height(1).bottom_margin(1);
 
     let title = format!(
-        "Portfolio - Total Value: ${:.2} | P/L: ${:.2} ({:+.2}%)",
-        total_value, total_pl, total_pl_pct
+        "Portfolio - Total Value: {} | P/L: {} ({})",
+        format_amount(totals.value, currency),
+        format_amount(totals.profit_loss, currency),
+        format_percent(Some(totals.profit_loss_pct), 2)
     );
+    // Paper trading gets a banner with the simulated cash, so it can't pass for the real thing
+    let title = match app.paper_account() {
+        Some(account) => {
+            let equity = account.available_cash() + totals.value;
+            let since_start = (account.starting_cash > 0.0)
+                .then(|| (equity / account.starting_cash - 1.0) * 100.0);
+            Span::styled(
+                format!(
+                    "PAPER — Cash: {} | Equity: {} ({} since start) | {}",
+                    format_amount(account.available_cash(), currency),
+                    format_amount(equity, currency),
+                    format_percent(since_start, 2),
+                    title,
+                ),
+                Style::default().fg(theme.warning).add_modifier(Modifier::BOLD),
+            )
+        }
+        None => Span::raw(title),
+    };
 
     let table = Table::new(rows)
         .header(header)
@@ -498,69 +963,472 @@ fn draw_portfolio<B: Backend>(f: &mut Frame<B>, app: &mut App, area: tui::layout
             Constraint::Length(12),  // Holdings
             Constraint::Length(12),  // Avg Buy
             Constraint::Length(14),  // Current Value
+            Constraint::Length(9),   // Alloc %
             Constraint::Length(14),  // Cost Basis
             Constraint::Length(12),  // P/L
             Constraint::Length(10),  // P/L %
+            Constraint::Length(12),  // Break-even
+            Constraint::Length(10),  // To B/E %
             Constraint::Length(10),  // 24h Change
+            Constraint::Length(13),  // Est. Yield/yr
         ])
         .column_spacing(1);
 
     // Render the table
+    app.page_size = table_page_size(chunks[0]);
     f.render_stateful_widget(table, chunks[0], &mut app.table_state);
+    if let Some(annotation) = annotation {
+        f.render_widget(Paragraph::new(annotation), chunks[1]);
+    }
 }
 
-fn draw_market<B: Backend>(f: &mut Frame<B>, area: tui::layout::Rect) {
-    let market_placeholder = Paragraph::new("Market - Coming Soon!")
-        .block(Block::default()
-            .title("Market")
-            .borders(Borders::ALL))
-        .style(Style::default().fg(Color::White))
-        .alignment(Alignment::Center);
-    f.render_widget(market_placeholder, area);
+fn draw_market<B: Backend>(f: &mut Frame<B>, app: &App, area: tui::layout::Rect) {
+    let theme = app.theme;
+    let block = Block::default()
+        .title(Span::styled(" Global Market Metrics ",
+            Style::default()
+                .fg(theme.text)
+                .add_modifier(Modifier::BOLD)
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.text));
+
+    let currency = app.config.convert_currency.as_str();
+    let metrics = match app.global_metrics.as_ref() {
+        Some(metrics) => metrics,
+        None => {
+            let loading = Paragraph::new("Loading market data...")
+                .block(block)
+                .style(Style::default().fg(theme.muted))
+                .alignment(Alignment::Center);
+            f.render_widget(loading, area);
+            return;
+        }
+    };
+    let quote = metrics.quote.get(currency);
+
+    let change_span = |change: Option<f64>| match change {
+        Some(v) => Span::styled(
+            format!("({})", format_percent(Some(v), 2)),
+            Style::default().fg(if v >= 0.0 { theme.positive } else { theme.negative })
+        ),
+        None => Span::raw(""),
+    };
+
+    let metrics_text = vec![
+        // Total Market Cap with 24h change
+        Spans::from(vec![
+            Span::styled("Total Market Cap", Style::default().fg(theme.muted)),
+            Span::raw("  "),
+            Span::styled(
+                format_market_cap(quote.map(|q| q.total_market_cap), currency),
+                Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
+            ),
+            Span::raw("  "),
+            change_span(quote.and_then(|q| q.total_market_cap_yesterday_percentage_change)),
+        ]),
+        Spans::from(vec![Span::raw("")]),  // Spacing
+
+        // 24h Volume with change
+        Spans::from(vec![
+            Span::styled("24h Volume", Style::default().fg(theme.muted)),
+            Span::raw("  "),
+            Span::styled(
+                format_volume(quote.map(|q| q.total_volume_24h), currency),
+                Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
+            ),
+            Span::raw("  "),
+            change_span(quote.and_then(|q| q.total_volume_24h_yesterday_percentage_change)),
+        ]),
+        Spans::from(vec![Span::raw("")]),  // Spacing
+
+        // Dominance
+        Spans::from(vec![
+            Span::styled("BTC Dominance", Style::default().fg(theme.muted)),
+            Span::raw("  "),
+            Span::styled(
+                format!("{}%", format_decimal(metrics.btc_dominance, 2)),
+                Style::default().fg(theme.header).add_modifier(Modifier::BOLD)
+            ),
+            Span::raw("  "),
+            Span::styled("ETH", Style::default().fg(theme.muted)),
+            Span::raw(" "),
+            Span::styled(
+                metrics.eth_dominance.map_or("N/A".to_string(), |v| format!("{}%", format_decimal(v, 2))),
+                Style::default().fg(theme.highlight)
+            ),
+        ]),
+        Spans::from(vec![Span::raw("")]),  // Spacing
+
+        // Active Cryptocurrencies
+        Spans::from(vec![
+            Span::styled("Active Cryptocurrencies", Style::default().fg(theme.muted)),
+            Span::raw("  "),
+            Span::styled(
+                format!("{}", metrics.active_cryptocurrencies),
+                Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
+            ),
+        ]),
+    ];
+
+    let metrics_block = Paragraph::new(metrics_text)
+        .block(block)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(metrics_block, area);
 }
 
 fn draw_help<B: Backend>(f: &mut Frame<B>, app: &App, area: tui::layout::Rect) {
+    let theme = app.theme;
     let text = match app.input_mode {
         InputMode::Normal => vec![
             Spans::from(vec![
-                Span::styled("q", Style::default().fg(Color::Yellow)),
+                Span::styled("q", Style::default().fg(theme.header)),
                 Span::raw(": Quit | "),
-                Span::styled("↓/j", Style::default().fg(Color::Yellow)),
+                Span::styled("↓/j", Style::default().fg(theme.header)),
                 Span::raw(" "),
-                Span::styled("↑/k", Style::default().fg(Color::Yellow)),
+                Span::styled("↑/k", Style::default().fg(theme.header)),
                 Span::raw(": Navigate | "),
-                Span::styled("Tab", Style::default().fg(Color::Yellow)),
+                Span::styled("Tab/S-Tab", Style::default().fg(theme.header)),
+                Span::raw(" "),
+                Span::styled("1-3", Style::default().fg(theme.header)),
                 Span::raw(": Switch View | "),
-                Span::styled("s", Style::default().fg(Color::Yellow)),
+                Span::styled("s", Style::default().fg(theme.header)),
                 Span::raw(": Sort | "),
-                Span::styled("d", Style::default().fg(Color::Yellow)),
+                Span::styled("d", Style::default().fg(theme.header)),
                 Span::raw(": Direction | "),
-                Span::styled("r", Style::default().fg(Color::Yellow)),
+                Span::styled("r", Style::default().fg(theme.header)),
                 Span::raw(": Refresh | "),
-                Span::styled("e", Style::default().fg(Color::Yellow)),
-                Span::raw(": Edit "),
+                Span::styled("e", Style::default().fg(theme.header)),
+                Span::raw(": Edit | "),
+                Span::styled("Enter", Style::default().fg(theme.header)),
+                Span::raw(": Details | "),
+                Span::styled("/", Style::default().fg(theme.header)),
+                Span::raw(": Filter | "),
+                Span::styled("?", Style::default().fg(theme.header)),
+                Span::raw(": Help "),
             ])
         ],
         InputMode::Editing => vec![
             Spans::from(vec![
-                Span::styled("Enter", Style::default().fg(Color::Yellow)),
+                Span::styled("Enter", Style::default().fg(theme.header)),
                 Span::raw(": Execute Command | "),
-                Span::styled("Esc", Style::default().fg(Color::Yellow)),
+                Span::styled("↑/↓", Style::default().fg(theme.header)),
+                Span::raw(": History | "),
+                Span::styled("Esc", Style::default().fg(theme.header)),
                 Span::raw(": Cancel"),
             ])
         ],
+        InputMode::Filter => vec![
+            Spans::from(vec![
+                Span::styled("Enter", Style::default().fg(theme.header)),
+                Span::raw(": Apply Filter | "),
+                Span::styled("Esc", Style::default().fg(theme.header)),
+                Span::raw(": Clear Filter"),
+            ])
+        ],
+        InputMode::Confirm => vec![
+            Spans::from(vec![
+                Span::styled("y", Style::default().fg(theme.header)),
+                Span::raw(": Confirm | "),
+                Span::styled("n/Esc", Style::default().fg(theme.header)),
+                Span::raw(": Cancel"),
+            ])
+        ],
+        InputMode::Detail => vec![
+            Spans::from(vec![
+                Span::styled("Esc", Style::default().fg(theme.header)),
+                Span::raw(": Close Details | "),
+                Span::styled("q", Style::default().fg(theme.header)),
+                Span::raw(": Quit"),
+            ])
+        ],
     };
 
+    let mut title = Vec::new();
+    if let Some(status) = &app.status_message {
+        title.push(Span::styled(
+            format!(" {} ", status),
+            Style::default().fg(theme.positive)
+        ));
+    }
+    if let Some(credits) = app.credits {
+        title.push(credits_span(credits, theme));
+    }
+    let mut block = Block::default().borders(Borders::ALL);
+    if !title.is_empty() {
+        block = block.title(Spans::from(title));
+    }
+
     let help = Paragraph::new(text)
-        .block(Block::default().borders(Borders::ALL))
+        .block(block)
         .alignment(Alignment::Center);
 
     f.render_widget(help, area);
 }
 
+/// API credits left, in the warning color once less than `LOW_CREDITS_FRACTION` of
+/// the plan's credits remain
+fn credits_span(credits: Credits, theme: Theme) -> Span<'static> {
+    let left = group_thousands(credits.remaining as f64, 0);
+    let (text, low) = match credits.limit.filter(|limit| *limit > 0) {
+        Some(limit) => (
+            format!(" API credits: {} of {} left ", left, group_thousands(limit as f64, 0)),
+            (credits.remaining as f64) < limit as f64 * LOW_CREDITS_FRACTION,
+        ),
+        None => (format!(" API credits: {} left ", left), false),
+    };
+    Span::styled(text, Style::default().fg(if low { theme.warning } else { theme.muted }))
+}
+
+/// Full keybinding and command reference, grouped by category
+const HELP_SECTIONS: &[(&str, &[(&str, &str)])] = &[
+    ("Navigation", &[
+        ("↑/k ↓/j", "Move selection"),
+        ("PgUp / PgDn", "Move a page"),
+        ("Home / End", "First / last row"),
+        ("Tab / Shift+Tab", "Next / previous view"),
+        ("1 2 3", "Watchlist / Portfolio / Market"),
+        ("Enter", "Coin details (watchlist)"),
+        ("y / Y", "Copy symbol / symbol and price"),
+        ("q / Ctrl+C", "Quit"),
+    ]),
+    ("Sorting & Filtering", &[
+        ("s", "Cycle sort column"),
+        ("d", "Toggle sort direction"),
+        ("/", "Filter watchlist (Esc clears)"),
+        ("w", "Toggle compact / wide watchlist"),
+        ("c", "Cycle summary change window (1h/24h/7d/30d)"),
+        ("a", "Cycle allocation list order (%/value/symbol)"),
+    ]),
+    ("Data", &[
+        ("r", "Refresh prices now"),
+        ("f", "Retry Fear & Greed fetch"),
+        ("x", "Dismiss error / status message"),
+        ("p", "Pause / resume automatic updates"),
+        ("+ / -", "Adjust selected holdings by quantity_step"),
+    ]),
+    ("Commands (press e)", &[
+        ("add <name> [-w|-p|-wp] [amt] [price]", "Track a token"),
+        ("rm <name> [-w|-p|-wp]", "Stop tracking a token"),
+        ("set <name> [owned <amt>] [avg <price>]", "Edit holdings"),
+        ("buy|sell <name> <qty> <price>", "Record a trade"),
+        ("alert <name> above|below <price>", "Price alert"),
+        ("top <n>", "Watch the n largest coins"),
+        ("pin|unpin <name>", "Keep a token at the top"),
+        ("tag <name> [tag...]", "Set tags (none clears)"),
+        ("note <name> [text]", "Set a note (none clears)"),
+        ("export <path>", "Write portfolio CSV"),
+        ("import <path>", "Load holdings from CSV"),
+        ("fng <days>", "Fear & Greed look-back window"),
+        ("fg-alert <fear> <greed> | off", "Fear & Greed zone alert"),
+        ("paper [reset]", "Toggle / reset paper trading"),
+        ("reload", "Re-read the config file"),
+        ("--dry-run (with add/rm/set)", "Preview changes without saving"),
+        ("↑/↓", "Command history"),
+    ]),
+];
+
+fn draw_help_overlay<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let theme = app.theme;
+    let popup_area = centered_rect(70, 80, area);
+    f.render_widget(Clear, popup_area);
+
+    let mut lines = Vec::new();
+    for (i, (section, bindings)) in HELP_SECTIONS.iter().enumerate() {
+        if i > 0 {
+            lines.push(Spans::from(""));
+        }
+        lines.push(Spans::from(Span::styled(
+            *section,
+            Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD),
+        )));
+        for (keys, description) in bindings.iter() {
+            lines.push(Spans::from(vec![
+                Span::styled(format!("  {:<40}", keys), Style::default().fg(theme.header)),
+                Span::styled(*description, Style::default().fg(theme.text)),
+            ]));
+        }
+    }
+
+    let help = Paragraph::new(lines)
+        .block(Block::default()
+            .title(Span::styled(
+                " Help (? or Esc to close) ",
+                Style::default().fg(theme.text).add_modifier(Modifier::BOLD),
+            ))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.accent)))
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(help, popup_area);
+}
+
+fn draw_detail<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let theme = app.theme;
+    let crypto = match app.detail_symbol.as_ref()
+        .and_then(|symbol| app.crypto_data.values().find(|crypto| &crypto.symbol == symbol))
+    {
+        Some(crypto) => crypto,
+        None => return,
+    };
+
+    let popup_area = centered_rect(70, 60, area);
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(Span::styled(
+            format!(" {} ({}) ", crypto.name, crypto.symbol),
+            Style::default().fg(theme.text).add_modifier(Modifier::BOLD)
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.accent));
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+
+    let annotation = app.token_for(crypto)
+        .filter(|token| !token.tags.is_empty() || token.note.is_some())
+        .map(|token| annotation_spans(&token.tags, token.note.as_deref(), theme));
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(if annotation.is_some() { 6 } else { 4 }),  // Price, changes, tags and note
+            Constraint::Min(5),     // Price chart
+        ])
+        .split(inner);
+
+    let currency = app.config.convert_currency.as_str();
+    let quote = app.quote(crypto);
+
+    let change_spans = |label: &'static str, value: Option<f64>| {
+        vec![
+            Span::styled(label, Style::default().fg(theme.muted)),
+            Span::raw(" "),
+            Span::styled(
+                format_percent(value, app.config.percent_precision),
+                match value {
+                    Some(v) if v >= 0.0 => Style::default().fg(theme.positive),
+                    Some(_) => Style::default().fg(theme.negative),
+                    None => Style::default(),
+                }
+            ),
+            Span::raw("  "),
+        ]
+    };
+
+    let mut info_text = vec![
+        Spans::from(vec![
+            Span::styled("Price", Style::default().fg(theme.muted)),
+            Span::raw("  "),
+            Span::styled(
+                quote.map_or("N/A".to_string(), |q| format_price(q.price, currency)),
+                Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
+            ),
+            Span::raw("  "),
+            Span::styled("Market Cap", Style::default().fg(theme.muted)),
+            Span::raw("  "),
+            Span::styled(
+                format_market_cap(quote.and_then(|q| q.market_cap), currency),
+                Style::default().fg(theme.highlight)
+            ),
+        ]),
+        Spans::from(vec![Span::raw("")]),  // Spacing
+        Spans::from(
+            [
+                ("1h", quote.and_then(|q| q.percent_change_1h)),
+                ("24h", quote.and_then(|q| q.percent_change_24h)),
+                ("7d", quote.and_then(|q| q.percent_change_7d)),
+                ("30d", quote.and_then(|q| q.percent_change_30d)),
+                ("90d", quote.and_then(|q| q.percent_change_90d)),
+            ]
+            .into_iter()
+            .flat_map(|(label, value)| change_spans(label, value))
+            .collect::<Vec<_>>()
+        ),
+    ];
+    if let Some(annotation) = annotation {
+        info_text.push(Spans::from(vec![Span::raw("")]));  // Spacing
+        info_text.push(annotation);
+    }
+
+    f.render_widget(
+        Paragraph::new(info_text).alignment(Alignment::Center),
+        chunks[0],
+    );
+
+    // Closing prices of the fetched daily candles, oldest first
+    let history: Vec<(String, f64)> = app.price_history.get(&crypto.symbol)
+        .map(|quotes| quotes.iter()
+            .filter_map(|q| q.quote.get(currency).map(|v| (q.time_open.clone(), v.close)))
+            .collect())
+        .unwrap_or_default();
+
+    if history.is_empty() {
+        let empty = Paragraph::new("No price history available")
+            .style(Style::default().fg(theme.muted))
+            .alignment(Alignment::Center)
+            .block(Block::default().title("7d Price").borders(Borders::TOP));
+        f.render_widget(empty, chunks[1]);
+        return;
+    }
+
+    let points: Vec<(f64, f64)> = history.iter()
+        .enumerate()
+        .map(|(i, (_, close))| (i as f64, *close))
+        .collect();
+    let min_price = history.iter().map(|(_, close)| *close).fold(f64::INFINITY, f64::min);
+    let max_price = history.iter().map(|(_, close)| *close).fold(f64::NEG_INFINITY, f64::max);
+    let padding = ((max_price - min_price) * 0.05).max(max_price.abs() * 0.001);
+
+    let line_color = if history.last().map(|(_, c)| *c) >= history.first().map(|(_, c)| *c) {
+        theme.positive
+    } else {
+        theme.negative
+    };
+
+    let datasets = vec![
+        Dataset::default()
+            .name(crypto.symbol.as_str())
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(line_color))
+            .data(&points),
+    ];
+
+    let date_label = |time_open: &str| {
+        chrono::DateTime::parse_from_rfc3339(time_open)
+            .map(|date| date.format("%b %-d").to_string())
+            .unwrap_or_default()
+    };
+
+    let chart = Chart::new(datasets)
+        .block(Block::default()
+            .title(format!("{}d Price", history.len()))
+            .borders(Borders::TOP))
+        .x_axis(Axis::default()
+            .style(Style::default().fg(theme.text))
+            .bounds([0.0, (points.len() - 1).max(1) as f64])
+            .labels(vec![
+                Span::styled(date_label(&history[0].0), Style::default().fg(theme.muted)),
+                Span::styled(date_label(&history[history.len() - 1].0), Style::default().fg(theme.muted)),
+            ]))
+        .y_axis(Axis::default()
+            .style(Style::default().fg(theme.text))
+            .bounds([min_price - padding, max_price + padding])
+            .labels(vec![
+                Span::from(format_price(min_price, currency)),
+                Span::from(format_price(max_price, currency)),
+            ]));
+
+    f.render_widget(chart, chunks[1]);
+}
+
 fn draw_input<B: Backend>(f: &mut Frame<B>, app: &App, area: tui::layout::Rect) {
+    let theme = app.theme;
     let input = Paragraph::new(app.input.as_ref())
-        .style(Style::default().fg(Color::Yellow))
+        .style(Style::default().fg(theme.header))
         .block(Block::default()
             .borders(Borders::ALL)
             .title("Command Input"));
@@ -568,55 +1436,56 @@ fn draw_input<B: Backend>(f: &mut Frame<B>, app: &App, area: tui::layout::Rect)
     f.render_widget(input, area);
 }
 
-fn draw_portfolio_summary<B: Backend>(f: &mut Frame<B>, app: &App, area: tui::layout::Rect) {
-    // Calculate portfolio totals
-    let owned_tokens: Vec<_> = app.config.tokens.iter()
-        .filter(|token| token.is_in_portfolio())
-        .filter_map(|token| {
-            app.crypto_data.values()
-                .find(|crypto| {
-                    let config_name = token.name.to_lowercase()
-                        .replace("-", " ")
-                        .replace("_", " ");
-                    let crypto_name = crypto.name.to_lowercase()
-                        .replace("-", " ")
-                        .replace("_", " ");
-                    config_name == crypto_name
-                })
-                .map(|crypto| (token, crypto))
-        })
-        .collect();
+fn draw_filter_input<B: Backend>(f: &mut Frame<B>, app: &App, area: tui::layout::Rect) {
+    let theme = app.theme;
+    let input = Paragraph::new(app.filter.as_deref().unwrap_or_default())
+        .style(Style::default().fg(theme.header))
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title("Filter Watchlist (symbol, name or tag)"));
 
-    let total_value: f64 = owned_tokens.iter()
-        .map(|(token_config, crypto)| {
-            token_config.owned.unwrap_or(0.0) * crypto.quote.get("USD").unwrap().price
-        })
-        .sum();
+    f.render_widget(input, area);
+}
 
-    let total_cost: f64 = owned_tokens.iter()
-        .map(|(token_config, _)| {
-            token_config.owned.unwrap_or(0.0) * token_config.avg_buy_price.unwrap_or(0.0)
-        })
-        .sum();
+fn draw_confirmation<B: Backend>(f: &mut Frame<B>, app: &App, area: tui::layout::Rect) {
+    let theme = app.theme;
+    let prompt = app.pending_confirmation.as_ref()
+        .map(|pending| pending.prompt.as_str())
+        .unwrap_or_default();
 
-    let total_pl = total_value - total_cost;
-    let total_pl_pct = if total_cost > 0.0 {
-        (total_pl / total_cost) * 100.0
-    } else {
-        0.0
-    };
+    let confirmation = Paragraph::new(prompt)
+        .style(Style::default().fg(theme.negative).add_modifier(Modifier::BOLD))
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title("Confirm"));
 
-    // Calculate 24h change
-    let total_24h_change: f64 = owned_tokens.iter()
-        .map(|(token_config, crypto)| {
-            let quote = crypto.quote.get("USD").unwrap();
-            let holdings = token_config.owned.unwrap_or(0.0);
-            let current_value = holdings * quote.price;
-            quote.percent_change_24h.unwrap_or(0.0) * current_value / 100.0
-        })
-        .sum();
-    
-    let total_24h_change_pct = (total_24h_change / total_value) * 100.0;
+    f.render_widget(confirmation, area);
+}
+
+fn draw_portfolio_summary<B: Backend>(f: &mut Frame<B>, app: &App, area: tui::layout::Rect) {
+    if !app.has_portfolio_tokens() {
+        f.render_widget(placeholder(
+            "Allocation and performance show up here once the portfolio has holdings",
+            "Portfolio Summary",
+            app.theme,
+        ), area);
+        return;
+    }
+
+    let theme = app.theme;
+    // Calculate portfolio totals
+    let positions = app.portfolio_positions();
+    let totals = PortfolioTotals::from_positions(&positions);
+    let total_value = totals.value;
+    let total_cost = totals.cost;
+    let total_pl = totals.profit_loss;
+    let total_pl_pct = totals.profit_loss_pct;
+
+    let window = app.summary_window;
+    let (total_change, total_change_pct) = totals.window_change(&positions, |quote| window.percent_change(quote));
+
+    let currency = app.config.convert_currency.as_str();
+    let symbol = currency_symbol(currency);
 
     // Create layout for the summary blocks
     let chunks = Layout::default()
@@ -632,105 +1501,141 @@ fn draw_portfolio_summary<B: Backend>(f: &mut Frame<B>, app: &App, area: tui::la
     let metrics_text = vec![
         // Net Worth
         Spans::from(vec![
-            Span::styled("Net Worth", Style::default().fg(Color::DarkGray)),
+            Span::styled("Net Worth", Style::default().fg(theme.muted)),
             Span::raw("  "),
             Span::styled(
-                format!("${:.2}", total_value),
-                Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
+                format_amount(total_value, currency),
+                Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
             ),
         ]),
         Spans::from(vec![Span::raw("")]),  // Spacing
 
         // Profit/Loss with percentage
         Spans::from(vec![
-            Span::styled("Profit/Loss", Style::default().fg(Color::DarkGray)),
+            Span::styled("Unrealized P/L", Style::default().fg(theme.muted)),
+            Span::raw("  "),
+            Span::styled(
+                format_amount(total_pl, currency),
+                Style::default()
+                    .fg(if total_pl >= 0.0 { theme.positive } else { theme.negative })
+                    .add_modifier(Modifier::BOLD)
+            ),
+            Span::raw("  "),
+            Span::styled(
+                format!("({})", format_percent(Some(total_pl_pct), 2)),
+                Style::default().fg(if total_pl >= 0.0 { theme.positive } else { theme.negative })
+            ),
+        ]),
+        Spans::from(vec![Span::raw("")]),  // Spacing
+
+        // Realized P/L from sells
+        Spans::from(vec![
+            Span::styled("Realized P/L", Style::default().fg(theme.muted)),
             Span::raw("  "),
             Span::styled(
-                format!("${:.2}", total_pl),
+                format_amount(totals.realized_profit_loss, currency),
                 Style::default()
-                    .fg(if total_pl >= 0.0 { Color::Green } else { Color::Red })
+                    .fg(if totals.realized_profit_loss >= 0.0 { theme.positive } else { theme.negative })
                     .add_modifier(Modifier::BOLD)
             ),
+        ]),
+        Spans::from(vec![Span::raw("")]),  // Spacing
+
+        // Projected staking yield
+        Spans::from(vec![
+            Span::styled("Est. Annual Yield", Style::default().fg(theme.muted)),
             Span::raw("  "),
             Span::styled(
-                format!("({:+.2}%)", total_pl_pct),
-                Style::default().fg(if total_pl >= 0.0 { Color::Green } else { Color::Red })
+                format_amount(totals.annual_yield, currency),
+                Style::default().fg(theme.positive).add_modifier(Modifier::BOLD)
             ),
         ]),
         Spans::from(vec![Span::raw("")]),  // Spacing
 
-        // 24h Change with percentage
+        // Change over the selected window with percentage
         Spans::from(vec![
-            Span::styled("24h Change", Style::default().fg(Color::DarkGray)),
+            Span::styled(format!("{} Change", window.label()), Style::default().fg(theme.muted)),
             Span::raw("  "),
             Span::styled(
-                format!("${:.2}", total_24h_change),
+                format_amount(total_change, currency),
                 Style::default()
-                    .fg(if total_24h_change >= 0.0 { Color::Green } else { Color::Red })
+                    .fg(if total_change >= 0.0 { theme.positive } else { theme.negative })
                     .add_modifier(Modifier::BOLD)
             ),
             Span::raw("  "),
             Span::styled(
-                format!("({:+.2}%)", total_24h_change_pct),
-                Style::default().fg(if total_24h_change >= 0.0 { Color::Green } else { Color::Red })
+                format!("({})", format_percent(Some(total_change_pct), 2)),
+                Style::default().fg(if total_change >= 0.0 { theme.positive } else { theme.negative })
             ),
         ]),
         Spans::from(vec![Span::raw("")]),  // Spacing
 
         // Cost Basis
         Spans::from(vec![
-            Span::styled("Cost Basis", Style::default().fg(Color::DarkGray)),
+            Span::styled("Cost Basis", Style::default().fg(theme.muted)),
             Span::raw("  "),
             Span::styled(
-                format!("${:.2}", total_cost),
-                Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
+                format_amount(total_cost, currency),
+                Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
             ),
         ]),
         Spans::from(vec![Span::raw("")]),  // Spacing
 
         // Assets Count
         Spans::from(vec![
-            Span::styled("Assets", Style::default().fg(Color::DarkGray)),
+            Span::styled("Assets", Style::default().fg(theme.muted)),
             Span::raw("  "),
             Span::styled(
-                format!("{}", owned_tokens.len()),
-                Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
+                format!("{}", positions.len()),
+                Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
             ),
             Span::raw(" "),
             Span::styled(
-                if owned_tokens.len() == 1 { "token" } else { "tokens" },
-                Style::default().fg(Color::DarkGray)
+                if positions.len() == 1 { "token" } else { "tokens" },
+                Style::default().fg(theme.muted)
             ),
         ]),
     ];
 
     let metrics_block = Paragraph::new(metrics_text)
         .block(Block::default()
-            .title(Span::styled(" Portfolio Metrics ", 
-                Style::default()
-                    .fg(Color::White)
-                    .add_modifier(Modifier::BOLD)
-            ))
+            .title(match app.paper_account() {
+                Some(_) => Span::styled(" PAPER Portfolio Metrics ",
+                    Style::default().fg(theme.warning).add_modifier(Modifier::BOLD)),
+                None => Span::styled(" Portfolio Metrics ", 
+                    Style::default()
+                        .fg(theme.text)
+                        .add_modifier(Modifier::BOLD)
+                ),
+            })
             .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::White)))
+            .border_style(Style::default().fg(theme.text)))
         .alignment(Alignment::Center)
         .wrap(Wrap { trim: true });
 
     // Allocations List
-    let mut allocations: Vec<_> = owned_tokens.iter()
-        .map(|(token_config, crypto)| {
-            let value = token_config.owned.unwrap_or(0.0) * crypto.quote.get("USD").unwrap().price;
+    // Nothing to share out while every holding is zero, e.g. after selling out
+    let mut allocations: Vec<_> = positions.iter()
+        .filter(|position| position.quote.is_some() && total_value > 0.0)
+        .map(|position| {
+            let value = position.current_value;
             let allocation = (value / total_value) * 100.0;
             (
-                crypto.symbol.clone(),
+                position.crypto.symbol.clone(),
                 allocation,
-                value
+                value,
+                position.target_allocation,
+                totals.rebalance_amount(position),
             )
         })
         .collect();
 
-    // Sort by allocation percentage (descending)
-    allocations.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    // Largest first for percentage and value, alphabetical for symbol
+    match app.allocation_sort {
+        AllocationSort::Percentage => allocations.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal)),
+        AllocationSort::Value => allocations.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap_or(std::cmp::Ordering::Equal)),
+        AllocationSort::Symbol => allocations.sort_by_key(|a| a.0.to_lowercase()),
+    }
 
     // Calculate dynamic bar width based on available space
     let available_width = chunks[1].width as usize;
@@ -743,7 +1648,7 @@ fn draw_portfolio_summary<B: Backend>(f: &mut Frame<B>, app: &App, area: tui::la
     };
 
     let allocation_items: Vec<ListItem> = allocations.iter()
-        .map(|(symbol, percentage, value)| {
+        .map(|(symbol, percentage, value, target, rebalance)| {
             let filled_width = ((percentage * bar_width as f64) / 100.0).round() as usize;
             let empty_width = bar_width - filled_width;
             
@@ -752,63 +1657,126 @@ fn draw_portfolio_summary<B: Backend>(f: &mut Frame<B>, app: &App, area: tui::la
                 Spans::from(vec![
                     Span::styled(
                         format!("{:<6}", symbol),  // Reduced symbol width
-                        Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+                        Style::default().fg(theme.header).add_modifier(Modifier::BOLD)
                     ),
                     Span::raw(" "),
                     Span::styled(
-                        format!("{:>4.1}%", percentage),  // Reduced percentage width
-                        Style::default().fg(Color::Cyan)
+                        format!("{:>5}", format!("{}%", format_decimal(*percentage, 1))),  // Reduced percentage width
+                        Style::default().fg(theme.highlight)
                     ),
                     Span::raw(" "),
                     Span::styled(
                         "█".repeat(filled_width),
-                        Style::default().fg(Color::Cyan)
+                        Style::default().fg(theme.accent)
                     ),
                     Span::styled(
                         "░".repeat(empty_width),
-                        Style::default().fg(Color::DarkGray)
+                        Style::default().fg(theme.muted)
                     ),
                     Span::raw(" "),
                     Span::styled(
-                        format!("${}", value.round() as i64),
-                        Style::default().fg(Color::White)
+                        format_amount(*value, currency),
+                        Style::default().fg(theme.text)
                     ),
                 ]),
-                // Empty line for spacing
-                Spans::from(vec![
-                    Span::raw(""),
-                ]),
+                // Rebalancing line, blank for spacing when no target is set
+                match (target, rebalance) {
+                    (Some(target), Some(amount)) => {
+                        let drift = percentage - target;
+                        let action = if *amount >= 0.0 { "buy" } else { "sell" };
+                        Spans::from(vec![
+                            Span::styled(
+                                format!("{:<6} target {}% · drift {} · ", "",
+                                    format_decimal(*target, 1), format_percent(Some(drift), 1)),
+                                Style::default().fg(theme.muted)
+                            ),
+                            Span::styled(
+                                format!("{} {}", action, format_amount(amount.abs(), currency)),
+                                Style::default().fg(if *amount >= 0.0 { theme.positive } else { theme.negative })
+                            ),
+                        ])
+                    }
+                    _ => Spans::from(vec![
+                        Span::raw(""),
+                    ]),
+                },
             ])
         })
         .collect();
 
+    let allocation_title = match app.config.target_allocation_total() {
+        Some(total) if total > 100.0 => Span::styled(
+            format!("Portfolio Allocation {} (targets sum to {}%)",
+                app.allocation_sort.label(), format_decimal(total, 1)),
+            Style::default().fg(theme.warning).add_modifier(Modifier::BOLD)
+        ),
+        _ => Span::raw(format!("Portfolio Allocation {}", app.allocation_sort.label())),
+    };
     let allocations_list = List::new(allocation_items)
         .block(Block::default()
-            .title("Portfolio Allocation")
+            .title(allocation_title)
             .borders(Borders::ALL))
         .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
         .highlight_symbol(">> ");
 
-    // Performance Block (placeholder for now)
-    let performance_block = Paragraph::new(vec![
-        Spans::from(vec![
-            Span::raw("Coming soon:"),
-        ]),
-        Spans::from(vec![
-            Span::raw("- Historical"),
-        ]),
-        Spans::from(vec![
-            Span::raw("- Price alerts"),
-        ]),
-        Spans::from(vec![
-            Span::raw("- Analytics"),
-        ]),
-    ])
-    .block(Block::default()
-        .title("Performance")
-        .borders(Borders::ALL))
-    .alignment(Alignment::Left)
-    .wrap(Wrap { trim: true });  // Fixed wrap
+    // Performance Block: net worth over time
+    let value_points: Vec<(f64, f64)> = app.value_history.iter()
+        .enumerate()
+        .map(|(i, point)| (i as f64, point.value))
+        .collect();
+    let min_value = value_points.iter().map(|(_, v)| *v).fold(f64::INFINITY, f64::min);
+    let max_value = value_points.iter().map(|(_, v)| *v).fold(f64::NEG_INFINITY, f64::max);
+    let date_label = |point: Option<&ValuePoint>| {
+        let date = point
+            .and_then(|p| chrono::DateTime::from_timestamp(p.timestamp, 0))
+            .map(|dt| dt.with_timezone(&chrono::Local).format("%b %-d").to_string())
+            .unwrap_or_default();
+        Span::styled(date, Style::default().fg(theme.muted))
+    };
+
+    let datasets = vec![
+        Dataset::default()
+            .name("Net Worth")
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(theme.accent))
+            .data(&value_points),
+    ];
+    let performance_title = match value_points.last() {
+        Some((_, current)) => format!(
+            "Performance | Now: {}{} | Min: {}{} | Max: {}{}",
+            symbol, group_thousands(*current, 0),
+            symbol, group_thousands(min_value, 0),
+            symbol, group_thousands(max_value, 0)
+        ),
+        None => "Performance (no history yet)".to_string(),
+    };
+    // Pad flat series so the line doesn't sit on the chart border
+    let padding = ((max_value - min_value) * 0.05).max(max_value.abs() * 0.01).max(1.0);
+    let (y_min, y_max) = if value_points.is_empty() {
+        (0.0, 1.0)
+    } else {
+        ((min_value - padding).max(0.0), max_value + padding)
+    };
+
+    let performance_block = Chart::new(datasets)
+        .block(Block::default()
+            .title(performance_title)
+            .borders(Borders::ALL))
+        .x_axis(Axis::default()
+            .style(Style::default().fg(theme.text))
+            .bounds([0.0, value_points.len().saturating_sub(1).max(1) as f64])
+            .labels(vec![
+                date_label(app.value_history.first()),
+                date_label(app.value_history.last()),
+            ]))
+        .y_axis(Axis::default()
+            .style(Style::default().fg(theme.text))
+            .bounds([y_min, y_max])
+            .labels(vec![
+                Span::styled(group_thousands(y_min, 0), Style::default().fg(theme.muted)),
+                Span::styled(group_thousands(y_max, 0), Style::default().fg(theme.muted)),
+            ]));
 
     // Render blocks
     f.render_widget(metrics_block, chunks[0]);
@@ -1,17 +1,18 @@
-use std::{io, time::Duration};
+use std::{io::{self, Write}, time::Duration};
 use tui::{
     backend::CrosstermBackend,
     Terminal,
 };
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    cursor,
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use std::fs;
-use anyhow::Result;
-use tokio::sync::mpsc;
-use chrono::Local;
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result};
+use rand::Rng;
+use tokio::sync::{mpsc, watch};
 
 mod app;
 mod models;
@@ -19,17 +20,124 @@ mod services;
 mod utils;
 
 use app::state::{App, InputMode, SortColumn};
-use app::ui;
+use app::{snapshot, ui};
 use models::config::Config;
-use services::logger;
+use services::api::{self, ApiError, PriceFetch};
+use services::demo;
+use services::logger::{self, LogLevel};
+use services::server;
+use utils::formatters::{self, NumberLocale};
+
+const CONFIG_ENV_VAR: &str = "COINTRACKER_CONFIG";
+const DEFAULT_CONFIG_PATH: &str = "config.json";
+/// Shortest pause before polling again after the API reports a rate limit
+const RATE_LIMIT_BACKOFF_SECS: u64 = 120;
+/// Room for fetch results that arrive while the UI is busy. Senders wait rather than drop
+/// when it's full, and the main loop drains everything queued on each pass, so this only
+/// needs to cover the background poll plus a few manual refreshes in flight at once
+const FETCH_CHANNEL_CAPACITY: usize = 8;
+
+struct Args {
+    config_path: PathBuf,
+    /// Print a single snapshot to stdout instead of starting the TUI
+    once: bool,
+    /// Print the snapshot as JSON (implies `once`)
+    json: bool,
+    /// Print build information and exit
+    version: bool,
+    /// Run offline on synthetic data with a throwaway config
+    demo: bool,
+}
+
+/// Parses command line arguments. The config path is taken from `--config <path>`,
+/// then the `COINTRACKER_CONFIG` env var, then `config.json` in the working directory
+fn parse_args() -> Result<Args> {
+    let mut config_path = None;
+    let mut once = false;
+    let mut json = false;
+    let mut version = false;
+    let mut demo = false;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config" | "-c" => {
+                let path = args.next().context("--config requires a path argument")?;
+                config_path = Some(PathBuf::from(path));
+            }
+            "--once" => once = true,
+            "--json" => json = true,
+            "--version" | "-V" => version = true,
+            "--demo" => demo = true,
+            other => anyhow::bail!("Unknown argument: {}", other),
+        }
+    }
+
+    let config_path = config_path
+        .or_else(|| std::env::var_os(CONFIG_ENV_VAR).map(PathBuf::from))
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG_PATH));
+
+    Ok(Args { config_path, once, json, version, demo })
+}
+
+/// Version, commit and target triple, for bug reports
+fn version_info() -> String {
+    format!(
+        "{} {} ({}, {})",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        option_env!("GIT_COMMIT").unwrap_or("unknown commit"),
+        env!("BUILD_TARGET"),
+    )
+}
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let mut args = parse_args()?;
+    if args.version {
+        println!("{}", version_info());
+        return Ok(());
+    }
+    // Held until main returns, which deletes the demo's scratch directory
+    let demo_dir = if args.demo { Some(demo::setup()?) } else { None };
+    if let Some(dir) = &demo_dir {
+        args.config_path = dir.config_path();
+    }
+
     // Load configuration
-    let config_str = fs::read_to_string("config.json")?;
-    let config: Config = serde_json::from_str(&config_str)?;
+    let mut config = Config::load(&args.config_path)?;
+    let merged = config.merge_duplicate_tokens();
+    if let Err(errors) = config.validate() {
+        anyhow::bail!(
+            "Invalid config file {}:\n  - {}",
+            args.config_path.display(),
+            errors.join("\n  - ")
+        );
+    }
+    logger::init(
+        PathBuf::from(&config.log_path),
+        LogLevel::from_name(&config.log_level),
+        config.max_log_bytes,
+    );
+    if merged > 0 {
+        config.save(&args.config_path).context("Failed to save deduplicated config")?;
+        logger::log_info("Config", &format!(
+            "Merged {} duplicate token entries in {}", merged, args.config_path.display()))?;
+    }
+    if let Some(total) = config.target_allocation_total().filter(|total| *total > 100.0) {
+        logger::log_error("Config", &format!(
+            "Target allocations sum to {:.1}%, more than 100%", total))?;
+    }
+    formatters::set_number_locale(NumberLocale::from_name(config.number_locale.as_deref()));
+    api::init_client(config.request_timeout_secs, config.resolved_https_proxy().as_deref())
+        .context("Failed to build HTTP client")?;
+
+    if args.once || args.json {
+        return print_snapshot(App::new(config, args.config_path), args.json).await;
+    }
 
     // Setup terminal
+    install_panic_hook();
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
@@ -37,7 +145,8 @@ async fn main() -> Result<()> {
     let mut terminal = Terminal::new(backend)?;
 
     // Create app and run it
-    let app = App::new(config);
+    let mut app = App::new(config, args.config_path);
+    app.load_cached_prices();
     let res = run_app(&mut terminal, app).await;
 
     // Restore terminal
@@ -57,117 +166,409 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// Restores the terminal before the default panic message is printed, so a panic
+/// inside the TUI doesn't leave the shell in raw mode on the alternate screen
+fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = disable_raw_mode();
+        let _ = execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture, cursor::Show);
+        default_hook(info);
+    }));
+}
+
+/// Fetches prices once and prints the watchlist and portfolio totals as plain text,
+/// or as JSON. A failed fetch is returned as an error so the process exits non-zero
+async fn print_snapshot(mut app: App, json: bool) -> Result<()> {
+    let fetch = app.fetch_prices().await.context("Failed to fetch prices")?;
+    app.set_prices(fetch);
+    if let Some(warning) = &app.last_error {
+        eprintln!("{}", warning);
+    }
+    if json {
+        println!("{}", snapshot::render_json(&app)?);
+    } else {
+        print!("{}", snapshot::render_text(&app));
+    }
+    Ok(())
+}
+
+/// The config as last saved by a command, or `fallback` if the file is currently
+/// unreadable or invalid
+fn current_config(path: &Path, fallback: &Config) -> Config {
+    Config::load(path).ok()
+        .map(|mut config| {
+            config.merge_duplicate_tokens();
+            config
+        })
+        .filter(|config| config.validate().is_ok())
+        .unwrap_or_else(|| fallback.clone())
+}
+
+/// How long a polling loop waits after a fetch: `None` (wait for `reload`) after a
+/// rejected key, which fails every time, and at least `RATE_LIMIT_BACKOFF_SECS` after a rate limit
+fn next_poll_delay(error: Option<&anyhow::Error>, interval_secs: u64) -> Option<Duration> {
+    match error.and_then(api::api_error) {
+        Some(ApiError::Auth(_)) => None,
+        Some(ApiError::RateLimited(_)) => Some(Duration::from_secs(interval_secs.max(RATE_LIMIT_BACKOFF_SECS))),
+        _ => Some(Duration::from_secs(interval_secs)),
+    }
+}
+
+/// Waits out a polling loop's `delay` (forever when `None`) or until `reload` applies a
+/// new config, returning whether it was the reload. `None` once the app has quit
+async fn wait_for_poll(delay: Option<Duration>, reloads: &mut watch::Receiver<u64>) -> Option<bool> {
+    match delay {
+        Some(delay) => tokio::select! {
+            _ = tokio::time::sleep(delay) => Some(false),
+            changed = reloads.changed() => changed.ok().map(|_| true),
+        },
+        None => reloads.changed().await.ok().map(|_| true),
+    }
+}
+
+/// `delay` moved by a random amount of up to `jitter_secs` either way, never below one second
+fn jittered(delay: Duration, jitter_secs: u64) -> Duration {
+    if jitter_secs == 0 {
+        return delay;
+    }
+    let jitter = Duration::from_secs(rand::thread_rng().gen_range(0..=jitter_secs * 2));
+    (delay + jitter).saturating_sub(Duration::from_secs(jitter_secs)).max(Duration::from_secs(1))
+}
+
+/// Shows a price fetch result, returning whether it moved a coin enough to ring the bell
+fn apply_price_result(app: &mut App, result: Result<PriceFetch>) -> bool {
+    app.is_loading = false;
+    match result {
+        Ok(new_data) => {
+            app.set_prices(new_data);
+            app.check_alerts();
+            app.check_large_moves()
+        }
+        Err(e) => {
+            app.last_error = Some(match api::api_error(&e) {
+                Some(ApiError::Auth(_)) => format!(
+                    "Invalid API key — check api_key in {} and run reload", app.config_path.display()),
+                Some(ApiError::RateLimited(message)) => format!(
+                    "Rate limited ({}) — refreshing less often for now", message),
+                _ => format!("Price fetch failed: {}", api::describe_error(&e)),
+            });
+            false
+        }
+    }
+}
+
 async fn run_app<B: tui::backend::Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<()> {
-    let (tx, mut rx) = mpsc::channel(1);
-    
-    // Fetch Fear & Greed data once at startup
-    let app_clone = App::new(app.config.clone());
-    if let Ok(fg_data) = app_clone.fetch_fear_greed().await {
-        app.fear_greed_data = fg_data;
+    let (tx, mut rx) = mpsc::channel(FETCH_CHANNEL_CAPACITY);
+    let (fg_tx, mut fg_rx) = mpsc::channel(FETCH_CHANNEL_CAPACITY);
+    let (rsi_tx, mut rsi_rx) = mpsc::channel(FETCH_CHANNEL_CAPACITY);
+
+    // Fetch global market metrics once at startup
+    let app_clone = App::new(app.config.clone(), app.config_path.clone());
+    match app_clone.fetch_global_metrics().await {
+        Ok(metrics) => app.global_metrics = Some(metrics),
+        Err(e) => logger::log_error("Global Metrics Fetch Error", &e.to_string()).unwrap_or(()),
     }
 
-    // Spawn crypto price fetching task
-    let config = app.config.clone();
+    // Counts applied `reload` commands; the polls below restart on the new config when
+    // it changes, including after a rejected key stopped them
+    let (reload_tx, reload_rx) = watch::channel(app.reloads);
+
+    // Spawn crypto price fetching task. Manual refreshes send on the same channel
+    let refresh_tx = tx.clone();
+    let mut reloads = reload_rx.clone();
+    let startup_config = app.config.clone();
+    let config_path = app.config_path.clone();
     tokio::spawn(async move {
+        // The fetch right after a reload counts as manual, so a pause doesn't hold it back
+        let mut reloaded = false;
         loop {
-            let app_clone = App::new(config.clone());
-            match app_clone.fetch_prices().await {
-                Ok(data) => {
-                    let _ = tx.send(data).await;
-                },
-                Err(e) => logger::log_error("Price Fetch Error", &e.to_string()).unwrap_or(()),
+            let config = current_config(&config_path, &startup_config);
+            let app_clone = App::new(config.clone(), config_path.clone());
+            let result = app_clone.fetch_prices().await;
+            if let Err(e) = &result {
+                logger::log_error("Price Fetch Error", &e.to_string()).unwrap_or(());
             }
-            tokio::time::sleep(Duration::from_secs(config.refresh_interval)).await;
+            // A rejected key parks polling until the next reload; `r` can still retry
+            let delay = next_poll_delay(result.as_ref().err(), config.price_refresh_interval)
+                .map(|delay| jittered(delay, config.refresh_jitter_secs));
+            let _ = tx.send((reloaded, result)).await;
+            reloaded = match wait_for_poll(delay, &mut reloads).await {
+                Some(reloaded) => reloaded,
+                None => break,
+            };
         }
     });
 
+    // Spawn fear & greed fetching task. Manual retries send on the same channel
+    let fg_retry_tx = fg_tx.clone();
+    let startup_config = app.config.clone();
+    let config_path = app.config_path.clone();
+    let mut reloads = reload_rx;
+    tokio::spawn(async move {
+        loop {
+            let config = current_config(&config_path, &startup_config);
+            let app_clone = App::new(config.clone(), config_path.clone());
+            let result = app_clone.fetch_fear_greed().await;
+            if let Err(e) = &result {
+                logger::log_error("Fear & Greed Fetch Error", &e.to_string()).unwrap_or(());
+            }
+            let delay = next_poll_delay(result.as_ref().err(), config.fear_greed_refresh_interval);
+            let _ = fg_tx.send(result).await;
+            if wait_for_poll(delay, &mut reloads).await.is_none() {
+                break;
+            }
+        }
+    });
+
+    // The optional HTTP server serves snapshots published from this loop, so it never
+    // waits on a fetch or command in progress here
+    let snapshots = app.config.serve_port.map(|port| {
+        let (snapshot_tx, snapshot_rx) = watch::channel(server::Snapshot::of(&app));
+        tokio::spawn(server::serve(port, snapshot_rx));
+        snapshot_tx
+    });
+
+    let mut held_prices = None;
     loop {
-        // Check for new price data
-        if let Ok(new_data) = rx.try_recv() {
-            app.crypto_data = new_data;
-            app.last_update = Some(Local::now());
+        let event = if event::poll(Duration::from_millis(100))? { Some(event::read()?) } else { None };
+        // Keys and fetch results are the only things that change what the server shows
+        let mut updated = event.is_some();
+
+        // Apply every queued price result in arrival order, so the newest one wins. While
+        // paused only manual refreshes get through; the newest automatic one waits for resume
+        let mut ring_bell = false;
+        if !app.paused {
+            if let Some(result) = held_prices.take() {
+                ring_bell |= apply_price_result(&mut app, result);
+                updated = true;
+            }
+        }
+        while let Ok((manual, result)) = rx.try_recv() {
+            if app.paused && !manual {
+                held_prices = Some(result);
+                continue;
+            }
+            if manual {
+                held_prices = None;
+            }
+            ring_bell |= apply_price_result(&mut app, result);
+            updated = true;
+        }
+
+        // Fetch RSI history for newly watched coins, one at a time to go easy on the API
+        if app.config.show_rsi {
+            let requests = app.take_rsi_requests();
+            if !requests.is_empty() {
+                let rsi_tx = rsi_tx.clone();
+                let app_clone = App::new(app.config.clone(), app.config_path.clone());
+                tokio::spawn(async move {
+                    for coin in requests {
+                        let rsi = app_clone.fetch_rsi(&coin).await.unwrap_or_else(|e| {
+                            logger::log_error("RSI Fetch Error", &format!("{}: {}", coin.symbol, e)).unwrap_or(());
+                            None
+                        });
+                        let _ = rsi_tx.send((coin.symbol, rsi)).await;
+                    }
+                });
+            }
+        }
+        while let Ok((symbol, rsi)) = rsi_rx.try_recv() {
+            app.rsi.insert(symbol, rsi);
+        }
+
+        // Check for new fear & greed data
+        while let Ok(result) = fg_rx.try_recv() {
+            match result {
+                Ok(fg_data) => {
+                    app.fear_greed_data = fg_data;
+                    app.fear_greed_error = None;
+                    ring_bell |= app.check_fear_greed_alert();
+                }
+                Err(e) => app.fear_greed_error = Some(api::describe_error(&e)),
+            }
+        }
+        // Background fetches report usage through the API layer
+        if let Some(credits) = api::latest_credits() {
+            app.credits = Some(credits);
+        }
+        if ring_bell {
+            let mut stdout = io::stdout();
+            let _ = stdout.write_all(b"\x07").and_then(|_| stdout.flush());
         }
 
         // Handle input
-        if event::poll(Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
-                match app.input_mode {
-                    InputMode::Normal => match key.code {
-                        KeyCode::Char('q') => return Ok(()),
-                        KeyCode::Down | KeyCode::Char('j') => app.next(),
-                        KeyCode::Up | KeyCode::Char('k') => app.previous(),
-                        KeyCode::Char('r') => {
-                            if let Ok(new_data) = app.fetch_prices().await {
-                                app.crypto_data = new_data;
-                                app.last_update = Some(Local::now());
-                            }
-                        },
-                        KeyCode::Char('d') => {
-                            app.sort_ascending = !app.sort_ascending;  // Toggle sort direction
-                        },
-                        KeyCode::Tab => app.next_tab(),
-                        KeyCode::Char('s') => {
-                            match app.tab_index {
-                                0 => {  // Watchlist tab
-                                    // Cycle through watchlist columns
-                                    app.sort_column = match app.sort_column {
-                                        SortColumn::Symbol => SortColumn::Price,
-                                        SortColumn::Price => SortColumn::Change1h,
-                                        SortColumn::Change1h => SortColumn::Change24h,
-                                        SortColumn::Change24h => SortColumn::Change7d,
-                                        SortColumn::Change7d => SortColumn::Change30d,
-                                        SortColumn::Change30d => SortColumn::Change90d,
-                                        SortColumn::Change90d => SortColumn::Volume24h,
-                                        SortColumn::Volume24h => SortColumn::VolumeChange,
-                                        SortColumn::VolumeChange => SortColumn::MarketCap,
-                                        SortColumn::MarketCap => SortColumn::Symbol,
-                                        _ => SortColumn::Symbol,
-                                    };
-                                },
-                                1 => {  // Portfolio tab
-                                    // Cycle through portfolio columns
-                                    app.portfolio_sort_column = match app.portfolio_sort_column {
-                                        SortColumn::Symbol => SortColumn::Price,
-                                        SortColumn::Price => SortColumn::Holdings,
-                                        SortColumn::Holdings => SortColumn::AvgBuy,
-                                        SortColumn::AvgBuy => SortColumn::CurrentValue,
-                                        SortColumn::CurrentValue => SortColumn::CostBasis,
-                                        SortColumn::CostBasis => SortColumn::ProfitLoss,
-                                        SortColumn::ProfitLoss => SortColumn::ProfitLossPercent,
-                                        SortColumn::ProfitLossPercent => SortColumn::Change24h,
-                                        SortColumn::Change24h => SortColumn::Symbol,
-                                        _ => SortColumn::Symbol,
-                                    };
-                                },
-                                _ => {}
-                            }
-                        },
-                        KeyCode::Char('e') => app.enter_edit_mode(),
-                        _ => {}
+        if let Some(Event::Key(key)) = event {
+            // Raw mode delivers Ctrl+C as a key press rather than SIGINT; leave the
+            // loop normally so main restores the terminal
+            if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                break;
+            }
+            match app.input_mode {
+                // The help overlay swallows every key until it's dismissed
+                _ if app.show_help => {
+                    if matches!(key.code, KeyCode::Esc | KeyCode::Char('?')) {
+                        app.show_help = false;
+                    }
+                }
+                InputMode::Normal => match key.code {
+                    KeyCode::Char('q') => break,
+                    KeyCode::Down | KeyCode::Char('j') => app.next(),
+                    KeyCode::Up | KeyCode::Char('k') => app.previous(),
+                    KeyCode::PageDown => app.page_down(),
+                    KeyCode::PageUp => app.page_up(),
+                    KeyCode::Home => app.select_first(),
+                    KeyCode::End => app.select_last(),
+                    KeyCode::Char('r') => {
+                        // Fetch in the background so the UI keeps drawing the spinner
+                        app.is_loading = true;
+                        let refresh_tx = refresh_tx.clone();
+                        let app_clone = App::new(app.config.clone(), app.config_path.clone());
+                        tokio::spawn(async move {
+                            let _ = refresh_tx.send((true, app_clone.fetch_prices().await)).await;
+                        });
                     },
-                    InputMode::Editing => match key.code {
-                        KeyCode::Enter => {
-                            if let Err(e) = app.process_command().await {
-                                app.last_error = Some(format!("Command error: {}", e));
-                            }
-                            app.exit_edit_mode();
+                    KeyCode::Char('y') => app.copy_selected(false),
+                    KeyCode::Char('Y') => app.copy_selected(true),
+                    KeyCode::Char('w') => app.compact_layout = Some(!app.compact_active),
+                    KeyCode::Char('f') => {
+                        // Retry just the fear & greed fetch, e.g. after a failure at startup
+                        let fg_retry_tx = fg_retry_tx.clone();
+                        let app_clone = App::new(app.config.clone(), app.config_path.clone());
+                        tokio::spawn(async move {
+                            let _ = fg_retry_tx.send(app_clone.fetch_fear_greed().await).await;
+                        });
+                    },
+                    KeyCode::Char('d') => {
+                        // Toggle the sort direction of the active tab only
+                        match app.tab_index {
+                            0 => app.watchlist_sort_ascending = !app.watchlist_sort_ascending,
+                            1 => app.portfolio_sort_ascending = !app.portfolio_sort_ascending,
+                            _ => {}
                         }
-                        KeyCode::Esc => {
-                            app.exit_edit_mode();
+                        app.restore_selection();
+                    },
+                    KeyCode::Tab => app.next_tab(),
+                    KeyCode::BackTab => app.previous_tab(),
+                    KeyCode::Char(c @ '1'..='3') => app.select_tab(c as usize - '1' as usize),
+                    KeyCode::Char('s') => {
+                        match app.tab_index {
+                            0 => {  // Watchlist tab
+                                // Cycle through the shown watchlist columns in display order
+                                let columns: Vec<SortColumn> = app.watchlist_columns().into_iter()
+                                    .filter(|column| app.is_column_visible(*column))
+                                    .collect();
+                                let next = columns.iter()
+                                    .position(|column| *column == app.sort_column)
+                                    .map_or(0, |i| (i + 1) % columns.len());
+                                if let Some(column) = columns.get(next) {
+                                    app.sort_column = *column;
+                                }
+                            },
+                            1 => {  // Portfolio tab
+                                // Cycle through portfolio columns
+                                app.portfolio_sort_column = match app.portfolio_sort_column {
+                                    SortColumn::Symbol => SortColumn::Price,
+                                    SortColumn::Price => SortColumn::Holdings,
+                                    SortColumn::Holdings => SortColumn::AvgBuy,
+                                    SortColumn::AvgBuy => SortColumn::CurrentValue,
+                                    SortColumn::CurrentValue => SortColumn::Allocation,
+                                    SortColumn::Allocation => SortColumn::CostBasis,
+                                    SortColumn::CostBasis => SortColumn::ProfitLoss,
+                                    SortColumn::ProfitLoss => SortColumn::ProfitLossPercent,
+                                    SortColumn::ProfitLossPercent => SortColumn::BreakEven,
+                                    SortColumn::BreakEven => SortColumn::ToBreakEven,
+                                    SortColumn::ToBreakEven => SortColumn::Change24h,
+                                    SortColumn::Change24h => SortColumn::AnnualYield,
+                                    SortColumn::AnnualYield => SortColumn::Symbol,
+                                    _ => SortColumn::Symbol,
+                                };
+                            },
+                            _ => {}
+                        }
+                        app.restore_selection();
+                    },
+                    KeyCode::Char('e') => app.enter_edit_mode(),
+                    KeyCode::Char('x') => app.clear_messages(),
+                    KeyCode::Char('p') => app.paused = !app.paused,
+                    KeyCode::Char('c') => app.summary_window = app.summary_window.next(),
+                    KeyCode::Char('a') => app.allocation_sort = app.allocation_sort.next(),
+                    KeyCode::Char('+') => app.adjust_selected_holdings(1.0),
+                    KeyCode::Char('-') => app.adjust_selected_holdings(-1.0),
+                    KeyCode::Char('?') => app.show_help = true,
+                    KeyCode::Char('/') if app.tab_index == 0 => app.enter_filter_mode(),
+                    KeyCode::Esc if app.filter.is_some() => app.clear_filter(),
+                    KeyCode::Enter if app.tab_index == 0 => app.open_detail().await,
+                    _ => {}
+                },
+                InputMode::Filter => match key.code {
+                    KeyCode::Enter => app.apply_filter(),
+                    KeyCode::Esc => app.clear_filter(),
+                    KeyCode::Char(c) => {
+                        if let Some(filter) = app.filter.as_mut() {
+                            filter.push(c);
+                        }
+                        app.restore_selection();
+                    }
+                    KeyCode::Backspace => {
+                        if let Some(filter) = app.filter.as_mut() {
+                            filter.pop();
+                        }
+                        app.restore_selection();
+                    }
+                    _ => {}
+                },
+                InputMode::Confirm => match key.code {
+                    KeyCode::Char('y') | KeyCode::Char('Y') => {
+                        if let Err(e) = app.confirm_pending().await {
+                            app.last_error = Some(format!("Command error: {}", e));
                         }
-                        KeyCode::Char(c) => {
-                            app.input.push(c);
+                    }
+                    KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => app.cancel_pending(),
+                    _ => {}
+                },
+                InputMode::Detail => match key.code {
+                    KeyCode::Esc | KeyCode::Enter => app.close_detail(),
+                    KeyCode::Char('q') => break,
+                    _ => {}
+                },
+                InputMode::Editing => match key.code {
+                    KeyCode::Enter => {
+                        if let Err(e) = app.process_command().await {
+                            app.last_error = Some(format!("Command error: {}", e));
                         }
-                        KeyCode::Backspace => {
-                            app.input.pop();
+                        // Commands that need confirmation switch to the prompt instead
+                        if app.input_mode == InputMode::Editing {
+                            app.exit_edit_mode();
                         }
-                        _ => {}
                     }
+                    KeyCode::Esc => {
+                        app.exit_edit_mode();
+                    }
+                    KeyCode::Char(c) => {
+                        app.input.push(c);
+                    }
+                    KeyCode::Backspace => {
+                        app.input.pop();
+                    }
+                    KeyCode::Up => app.history_previous(),
+                    KeyCode::Down => app.history_next(),
+                    _ => {}
                 }
             }
         }
 
+        // Restart the polls after a `reload`
+        reload_tx.send_if_modified(|seen| std::mem::replace(seen, app.reloads) != app.reloads);
+
         // Draw UI
         terminal.draw(|f| ui::draw(f, &mut app))?;
+        if let Some(snapshots) = snapshots.as_ref().filter(|_| updated) {
+            server::publish(snapshots, &app);
+        }
     }
-} 
+
+    // Remembered for the next session's change since the last visit
+    app.save_last_viewed();
+    Ok(())
+}
@@ -1,9 +1,194 @@
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 
+use crate::models::crypto::CryptoData;
+
+/// Names accepted in `columns`, in the default watchlist order
+pub const WATCHLIST_COLUMN_NAMES: [&str; 10] = [
+    "symbol", "price", "change_1h", "change_24h", "change_7d", "change_30d", "change_90d",
+    "volume_24h", "volume_change", "market_cap",
+];
+
 fn default_true() -> bool {
     true
 }
 
+fn default_price_refresh_interval() -> u64 {
+    60
+}
+
+fn default_fear_greed_refresh_interval() -> u64 {
+    3600
+}
+
+fn default_provider() -> String {
+    "coinmarketcap".to_string()
+}
+
+fn default_convert_currency() -> String {
+    "USD".to_string()
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+fn default_retry_base_ms() -> u64 {
+    500
+}
+
+fn default_request_timeout_secs() -> u64 {
+    10
+}
+
+fn default_batch_size() -> usize {
+    100
+}
+
+fn default_holdings_precision() -> usize {
+    4
+}
+
+fn default_percent_precision() -> usize {
+    2
+}
+
+fn default_quantity_step() -> f64 {
+    1.0
+}
+
+fn default_paper_cash() -> f64 {
+    10_000.0
+}
+
+fn default_fear_greed_ma_window() -> usize {
+    7
+}
+
+fn default_log_path() -> String {
+    "crypto_tracker.log".to_string()
+}
+
+fn default_log_level() -> String {
+    "info".to_string()
+}
+
+fn default_max_log_bytes() -> u64 {
+    10 * 1024 * 1024
+}
+
+/// Percentage bands used to color price changes
+#[derive(Debug, Deserialize, Serialize, Clone, Copy)]
+#[serde(default)]
+pub struct ChangeThresholds {
+    /// Moves smaller than this (in either direction) are shown as neutral noise
+    pub neutral: f64,
+    /// Moves at least this large are shown in bold
+    pub strong: f64,
+}
+
+impl Default for ChangeThresholds {
+    fn default() -> Self {
+        ChangeThresholds {
+            neutral: 1.0,
+            strong: 10.0,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertDirection {
+    Above,
+    Below,
+}
+
+/// Notifies once when a token's price crosses `price` in the given direction
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Alert {
+    pub token: String,
+    pub direction: AlertDirection,
+    pub price: f64,
+    /// Set once the alert has fired; cleared when the price moves back across
+    /// the threshold so the next crossing notifies again
+    #[serde(default)]
+    pub triggered: bool,
+}
+
+impl Alert {
+    pub fn is_crossed(&self, price: f64) -> bool {
+        match self.direction {
+            AlertDirection::Above => price >= self.price,
+            AlertDirection::Below => price <= self.price,
+        }
+    }
+}
+
+/// The extreme bands of the fear & greed index
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum FearGreedZone {
+    Fear,
+    Greed,
+}
+
+/// Notifies once when the fear & greed index enters extreme fear (at or below `fear`)
+/// or extreme greed (at or above `greed`)
+#[derive(Debug, Deserialize, Serialize, Clone, Copy)]
+pub struct FearGreedAlert {
+    pub fear: u64,
+    pub greed: u64,
+    /// The zone last notified about; cleared once the index leaves it so the next
+    /// entry notifies again
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notified: Option<FearGreedZone>,
+}
+
+impl FearGreedAlert {
+    pub fn zone(&self, value: u64) -> Option<FearGreedZone> {
+        if value <= self.fear {
+            Some(FearGreedZone::Fear)
+        } else if value >= self.greed {
+            Some(FearGreedZone::Greed)
+        } else {
+            None
+        }
+    }
+}
+
+/// How sells are matched against earlier buys when deriving the cost basis and
+/// realized P/L from a transaction history
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CostBasisMethod {
+    /// Every unit costs the weighted average of the buys so far
+    #[default]
+    Average,
+    /// Sells use up the oldest remaining buys first
+    Fifo,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TransactionKind {
+    Buy,
+    Sell,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Transaction {
+    #[serde(rename = "type")]
+    pub kind: TransactionKind,
+    pub quantity: f64,
+    pub price: f64,
+    /// Unix timestamp (seconds) of when the transaction was recorded
+    pub timestamp: i64,
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct TokenConfig {
     pub name: String,
@@ -15,6 +200,27 @@ pub struct TokenConfig {
     pub in_watchlist: bool,
     #[serde(default = "default_true")]
     pub in_portfolio: bool,
+    /// Pinned tokens are listed first on the watchlist regardless of sort
+    #[serde(default)]
+    pub pinned: bool,
+    /// Buy/sell history. When present, `owned` and `avg_buy_price` are derived from it
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transactions: Vec<Transaction>,
+    /// Desired share of the portfolio value, in percent, for rebalancing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_allocation: Option<f64>,
+    /// Yearly staking or yield rate, in percent, for the projected annual yield
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub staking_apr: Option<f64>,
+    /// Free-form labels such as "staking" or "long-term"; the watchlist filter matches them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Why the token is held or watched
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// Watchlist section, e.g. "L1" or "Stablecoins", used when `group_watchlist` is on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
 }
 
 impl TokenConfig {
@@ -25,12 +231,883 @@ impl TokenConfig {
     pub fn is_in_watchlist(&self) -> bool {
         self.in_watchlist
     }
+
+    /// Appends a transaction and recomputes `owned` and `avg_buy_price` from the history.
+    /// A position that only has the flat fields is first recorded as an opening buy so
+    /// its existing cost basis carries over
+    pub fn record_transaction(&mut self, transaction: Transaction, method: CostBasisMethod) {
+        if self.transactions.is_empty() {
+            if let Some(owned) = self.owned.filter(|owned| *owned > 0.0) {
+                self.transactions.push(Transaction {
+                    kind: TransactionKind::Buy,
+                    quantity: owned,
+                    price: self.avg_buy_price.unwrap_or(0.0),
+                    timestamp: transaction.timestamp,
+                });
+            }
+        }
+        self.transactions.push(transaction);
+
+        let (owned, avg_buy_price, _) = self.replay_transactions(method);
+        self.owned = Some(owned);
+        self.avg_buy_price = Some(avg_buy_price);
+    }
+
+    /// Average buy price of the current holdings and the profit or loss locked in by
+    /// sells, with sells matched to buys by `method`. Positions without a transaction
+    /// history use `avg_buy_price` and have no realized P/L
+    pub fn cost_basis(&self, method: CostBasisMethod) -> (f64, f64) {
+        if self.transactions.is_empty() {
+            return (self.avg_buy_price.unwrap_or(0.0), 0.0);
+        }
+        let (_, avg_buy_price, realized) = self.replay_transactions(method);
+        (avg_buy_price, realized)
+    }
+
+    /// Merges a duplicate entry for the same token into this one
+    fn absorb(&mut self, other: TokenConfig, method: CostBasisMethod) {
+        // Holdings entered without a history, which become buys if the other side has one
+        let plain_lot = |token: &TokenConfig| {
+            token.owned.filter(|owned| token.transactions.is_empty() && *owned > 0.0)
+                .map(|owned| (owned, token.avg_buy_price.unwrap_or(0.0)))
+        };
+        let plain_lots = [plain_lot(self), plain_lot(&other)];
+
+        self.in_watchlist |= other.in_watchlist;
+        self.in_portfolio |= other.in_portfolio;
+        self.pinned |= other.pinned;
+        self.target_allocation = self.target_allocation.or(other.target_allocation);
+        self.staking_apr = self.staking_apr.or(other.staking_apr);
+        self.note = self.note.take().or(other.note);
+        self.group = self.group.take().or(other.group);
+        for tag in other.tags {
+            if !self.tags.iter().any(|t| t.eq_ignore_ascii_case(&tag)) {
+                self.tags.push(tag);
+            }
+        }
+
+        // Weight each average buy price by the amount bought at it
+        let lots = [(self.owned, self.avg_buy_price), (other.owned, other.avg_buy_price)];
+        let owned = match (self.owned, other.owned) {
+            (None, None) => None,
+            (a, b) => Some(a.unwrap_or(0.0) + b.unwrap_or(0.0)),
+        };
+        let (weight, cost) = lots.iter()
+            .filter_map(|lot| match lot {
+                (Some(owned), Some(avg)) => Some((*owned, owned * avg)),
+                _ => None,
+            })
+            .fold((0.0, 0.0), |(weight, cost), (w, c)| (weight + w, cost + c));
+        self.avg_buy_price = if weight > 0.0 {
+            Some(cost / weight)
+        } else {
+            self.avg_buy_price.or(other.avg_buy_price)
+        };
+        self.owned = owned;
+
+        // A transaction history is authoritative for holdings, so recompute from it. Plain
+        // holdings join it as buys at their average price, ahead of the first transaction
+        if !self.transactions.is_empty() || !other.transactions.is_empty() {
+            let first = self.transactions.iter().chain(&other.transactions).map(|t| t.timestamp).min();
+            let mut transactions: Vec<Transaction> = plain_lots.into_iter().flatten()
+                .map(|(quantity, price)| Transaction {
+                    kind: TransactionKind::Buy,
+                    quantity,
+                    price,
+                    timestamp: first.unwrap_or_default(),
+                })
+                .collect();
+            transactions.append(&mut self.transactions);
+            transactions.extend(other.transactions);
+            transactions.sort_by_key(|t| t.timestamp);
+            self.transactions = transactions;
+
+            let (quantity, avg_price, _) = self.replay_transactions(method);
+            self.owned = Some(quantity);
+            self.avg_buy_price = Some(avg_price);
+        }
+    }
+
+    /// Walks the history, returning the remaining quantity, its average buy price and
+    /// the realized P/L under `method`
+    fn replay_transactions(&self, method: CostBasisMethod) -> (f64, f64, f64) {
+        match method {
+            CostBasisMethod::Average => self.replay_average(),
+            CostBasisMethod::Fifo => self.replay_fifo(),
+        }
+    }
+
+    fn replay_average(&self) -> (f64, f64, f64) {
+        let mut quantity = 0.0;
+        let mut avg_price = 0.0;
+        let mut realized = 0.0;
+
+        for transaction in &self.transactions {
+            match transaction.kind {
+                TransactionKind::Buy => {
+                    let total_cost = quantity * avg_price + transaction.quantity * transaction.price;
+                    quantity += transaction.quantity;
+                    avg_price = if quantity > 0.0 { total_cost / quantity } else { 0.0 };
+                }
+                TransactionKind::Sell => {
+                    let sold = transaction.quantity.min(quantity);
+                    realized += sold * (transaction.price - avg_price);
+                    quantity -= sold;
+                }
+            }
+        }
+
+        (quantity, avg_price, realized)
+    }
+
+    /// Keeps each buy as a lot and takes sells out of the oldest lots first, so the
+    /// remaining holdings cost what their own buys did
+    fn replay_fifo(&self) -> (f64, f64, f64) {
+        // (quantity left, buy price), oldest first
+        let mut lots: VecDeque<(f64, f64)> = VecDeque::new();
+        let mut realized = 0.0;
+
+        for transaction in &self.transactions {
+            match transaction.kind {
+                TransactionKind::Buy => lots.push_back((transaction.quantity, transaction.price)),
+                TransactionKind::Sell => {
+                    let mut to_sell = transaction.quantity;
+                    while to_sell > 0.0 {
+                        let Some(lot) = lots.front_mut() else { break };
+                        let matched = lot.0.min(to_sell);
+                        realized += matched * (transaction.price - lot.1);
+                        lot.0 -= matched;
+                        to_sell -= matched;
+                        if lot.0 <= 0.0 {
+                            lots.pop_front();
+                        }
+                    }
+                }
+            }
+        }
+
+        let quantity: f64 = lots.iter().map(|(quantity, _)| quantity).sum();
+        let cost: f64 = lots.iter().map(|(quantity, price)| quantity * price).sum();
+        let avg_price = if quantity > 0.0 { cost / quantity } else { 0.0 };
+        (quantity, avg_price, realized)
+    }
+
+    /// Whether this entry refers to the given CMC coin. Names are normally CMC slugs
+    /// ("bitcoin-cash"), so an exact slug match wins; display names ("Bitcoin Cash")
+    /// and symbols ("BCH") are accepted as well
+    pub fn matches_crypto(&self, crypto: &CryptoData) -> bool {
+        if let Some(slug) = &crypto.slug {
+            if self.name.eq_ignore_ascii_case(slug) {
+                return true;
+            }
+        }
+
+        let normalize = |name: &str| name.to_lowercase().replace(['-', '_'], " ");
+        normalize(&self.name) == normalize(&crypto.name)
+            || self.name.eq_ignore_ascii_case(&crypto.symbol)
+    }
+}
+
+/// Simulated account that `buy`/`sell` trade against while paper trading is on. Kept
+/// apart from `tokens` so simulated trades never touch real holdings
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PaperAccount {
+    /// Cash the account starts with, and goes back to on `paper reset`
+    #[serde(default = "default_paper_cash")]
+    pub starting_cash: f64,
+    /// Cash left after the simulated trades; `starting_cash` until the first trade
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cash: Option<f64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub positions: Vec<TokenConfig>,
+}
+
+impl Default for PaperAccount {
+    fn default() -> Self {
+        PaperAccount {
+            starting_cash: default_paper_cash(),
+            cash: None,
+            positions: Vec::new(),
+        }
+    }
+}
+
+impl PaperAccount {
+    pub fn available_cash(&self) -> f64 {
+        self.cash.unwrap_or(self.starting_cash)
+    }
+
+    /// Applies a simulated trade, paying for buys out of the account's cash and adding
+    /// the proceeds of sells to it
+    pub fn trade(&mut self, name: &str, transaction: Transaction, method: CostBasisMethod) -> Result<(), String> {
+        let kind = transaction.kind;
+        let amount = transaction.quantity * transaction.price;
+        let cash = self.available_cash();
+        let position = self.positions.iter_mut()
+            .find(|token| token.name.eq_ignore_ascii_case(name));
+
+        match (position, kind) {
+            (_, TransactionKind::Buy) if amount > cash => {
+                return Err(format!("Not enough paper cash: the buy costs {:.2}, {:.2} left", amount, cash));
+            }
+            (Some(position), TransactionKind::Sell) if transaction.quantity > position.owned.unwrap_or(0.0) => {
+                return Err(format!(
+                    "Cannot sell {} {}, only {} held on paper", transaction.quantity, name, position.owned.unwrap_or(0.0)));
+            }
+            (None, TransactionKind::Sell) => {
+                return Err(format!("No paper position in '{}'", name));
+            }
+            (Some(position), _) => position.record_transaction(transaction, method),
+            (None, TransactionKind::Buy) => {
+                let mut position = TokenConfig {
+                    name: name.to_string(),
+                    owned: None,
+                    avg_buy_price: None,
+                    in_watchlist: false,
+                    in_portfolio: true,
+                    pinned: false,
+                    transactions: Vec::new(),
+                    target_allocation: None,
+                    staking_apr: None,
+                    tags: Vec::new(),
+                    note: None,
+                    group: None,
+                };
+                position.record_transaction(transaction, method);
+                self.positions.push(position);
+            }
+        }
+
+        self.cash = Some(match kind {
+            TransactionKind::Buy => cash - amount,
+            TransactionKind::Sell => cash + amount,
+        });
+        Ok(())
+    }
+
+    /// Back to the starting cash with no positions
+    pub fn reset(&mut self) {
+        self.cash = None;
+        self.positions.clear();
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Config {
+    /// Data source: "coinmarketcap" (default) or "coingecko"
+    #[serde(default = "default_provider")]
+    pub provider: String,
     pub api_key: String,
     pub tokens: Vec<TokenConfig>,
-    pub refresh_interval: u64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alerts: Vec<Alert>,
+    /// Seconds between price fetches (`refresh_interval` in older configs)
+    #[serde(alias = "refresh_interval", default = "default_price_refresh_interval")]
+    pub price_refresh_interval: u64,
+    /// Up to this many seconds are randomly added to or taken off each price refresh
+    /// interval, so several instances don't poll in lockstep
+    #[serde(default)]
+    pub refresh_jitter_secs: u64,
+    /// Seconds between fear & greed fetches; the index only changes daily
+    #[serde(default = "default_fear_greed_refresh_interval")]
+    pub fear_greed_refresh_interval: u64,
     pub fear_and_greed_limit: String,
+    /// Points averaged by the moving average line on the fear & greed chart; 0 or 1 hides it
+    #[serde(default = "default_fear_greed_ma_window")]
+    pub fear_greed_ma_window: usize,
+    #[serde(default = "default_convert_currency")]
+    pub convert_currency: String,
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    #[serde(default = "default_retry_base_ms")]
+    pub retry_base_ms: u64,
+    /// Seconds before an HTTP request is abandoned
+    #[serde(default = "default_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+    /// Proxy for HTTPS requests; falls back to the HTTPS_PROXY environment variable
+    #[serde(default)]
+    pub https_proxy: Option<String>,
+    /// Color theme name: "dark" (default), "light" or "solarized"
+    #[serde(default)]
+    pub theme: Option<String>,
+    /// Language code picking the decimal and grouping separators, e.g. "de" for
+    /// 1.234,56. US formatting when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub number_locale: Option<String>,
+    /// Show a bar chart column of 24h changes on the watchlist
+    #[serde(default = "default_true")]
+    pub show_change_bars: bool,
+    /// Shade every other row of the watchlist and portfolio tables
+    #[serde(default)]
+    pub stripe_rows: bool,
+    /// Show an RSI column on the watchlist, from daily candles fetched once a day per coin
+    #[serde(default)]
+    pub show_rsi: bool,
+    /// Size watchlist columns to their content instead of the fixed layout widths
+    #[serde(default)]
+    pub auto_size_columns: bool,
+    /// Watchlist columns to show, in order, from `WATCHLIST_COLUMN_NAMES`. Every column
+    /// in the default order when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub columns: Vec<String>,
+    /// Split the watchlist into sections by each token's `group`
+    #[serde(default)]
+    pub group_watchlist: bool,
+    /// Leave tokens without holdings out of the portfolio views
+    #[serde(default)]
+    pub hide_empty_positions: bool,
+    /// Minimum decimal places for portfolio holdings; small amounts get more
+    #[serde(default = "default_holdings_precision")]
+    pub holdings_precision: usize,
+    /// Decimal places for percentage columns
+    #[serde(default = "default_percent_precision")]
+    pub percent_precision: usize,
+    /// Amount `+`/`-` add to or take off the selected portfolio position's holdings
+    #[serde(default = "default_quantity_step")]
+    pub quantity_step: f64,
+    /// How sells are matched to buys for the cost basis and realized P/L: "average"
+    /// (default) or "fifo"
+    #[serde(default)]
+    pub cost_basis_method: CostBasisMethod,
+    #[serde(default)]
+    pub change_thresholds: ChangeThresholds,
+    /// Ring the terminal bell when a watched token's 24h change reaches this many
+    /// percent (either direction). Disabled when absent
+    #[serde(default)]
+    pub bell_on_change_pct: Option<f64>,
+    /// Fear & greed thresholds to notify about. Disabled when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fear_greed_alert: Option<FearGreedAlert>,
+    /// Simulated account for paper trading; created the first time paper mode is turned on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paper: Option<PaperAccount>,
+    /// Port for a local HTTP server exposing the portfolio and prices as JSON. Off when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub serve_port: Option<u16>,
+    #[serde(default = "default_log_path")]
+    pub log_path: String,
+    /// Most verbose level written to the log: "error", "info" or "debug"
+    #[serde(default = "default_log_level")]
+    pub log_level: String,
+    /// Size at which the log file is rotated to `<log_path>.1`
+    #[serde(default = "default_max_log_bytes")]
+    pub max_log_bytes: u64,
+}
+
+/// Whether `path` names a TOML config; anything else is read and written as JSON
+fn is_toml(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("toml"))
+}
+
+impl Config {
+    /// Reads and parses a config file, as TOML for a `.toml` extension and JSON otherwise.
+    /// Validation is left to the caller
+    pub fn load(path: &Path) -> Result<Config> {
+        let config_str = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        let parsed = if is_toml(path) {
+            toml::from_str(&config_str).map_err(anyhow::Error::from)
+        } else {
+            serde_json::from_str(&config_str).map_err(anyhow::Error::from)
+        };
+        parsed.with_context(|| format!("Failed to parse config file {}", path.display()))
+    }
+
+    /// Checks the settings that would otherwise only fail later (or silently misbehave),
+    /// collecting every problem rather than stopping at the first
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+
+        if self.price_refresh_interval < 1 {
+            errors.push("price_refresh_interval must be at least 1 second".to_string());
+        }
+        if self.fear_greed_refresh_interval < 1 {
+            errors.push("fear_greed_refresh_interval must be at least 1 second".to_string());
+        }
+        match self.fear_and_greed_limit.parse::<u32>() {
+            Ok(limit) if limit > 0 => {}
+            _ => errors.push(format!(
+                "fear_and_greed_limit must be a positive integer, got '{}'", self.fear_and_greed_limit)),
+        }
+        // CoinGecko works without a key, and the demo provider makes no requests
+        let keyless = ["coingecko", "demo"].iter().any(|name| self.provider.eq_ignore_ascii_case(name));
+        if self.api_key.trim().is_empty() && !keyless {
+            errors.push("api_key must not be empty".to_string());
+        }
+
+        if self.quantity_step.is_nan() || self.quantity_step <= 0.0 {
+            errors.push(format!("quantity_step must be positive, got {}", self.quantity_step));
+        }
+
+        if let Some(paper) = &self.paper {
+            if paper.starting_cash.is_nan() || paper.starting_cash < 0.0 {
+                errors.push(format!("paper.starting_cash must not be negative, got {}", paper.starting_cash));
+            }
+        }
+
+        for column in &self.columns {
+            if !WATCHLIST_COLUMN_NAMES.contains(&column.as_str()) {
+                errors.push(format!(
+                    "unknown column '{}', expected one of: {}", column, WATCHLIST_COLUMN_NAMES.join(", ")));
+            }
+        }
+
+        if let Some(alert) = self.fear_greed_alert {
+            if alert.fear >= alert.greed || alert.greed > 100 {
+                errors.push(format!(
+                    "fear_greed_alert needs fear < greed <= 100, got fear {} and greed {}", alert.fear, alert.greed));
+            }
+        }
+
+        for token in &self.tokens {
+            if let Some(target) = token.target_allocation {
+                if !(0.0..=100.0).contains(&target) {
+                    errors.push(format!(
+                        "target_allocation of '{}' must be between 0 and 100, got {}", token.name, target));
+                }
+            }
+            if let Some(apr) = token.staking_apr {
+                if apr.is_nan() || apr < 0.0 {
+                    errors.push(format!("staking_apr of '{}' must not be negative, got {}", token.name, apr));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Writes the config to a temp file next to `path` and renames it into place, so an
+    /// interrupted write can never leave a truncated config behind. The format follows the
+    /// extension, as in [`Config::load`]. The file keeps the permissions of the one it
+    /// replaces; a new one is readable by the owner only, since it holds the API key
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let config_str = if is_toml(path) {
+            toml::to_string_pretty(self)?
+        } else {
+            serde_json::to_string_pretty(self)?
+        };
+
+        let file_name = path.file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "config.json".to_string());
+        let temp_path = path.with_file_name(format!(".{}.tmp", file_name));
+
+        let result = (|| -> Result<()> {
+            let mut options = std::fs::OpenOptions::new();
+            options.write(true).create(true).truncate(true);
+            #[cfg(unix)]
+            std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+            let mut file = options.open(&temp_path)?;
+            if let Ok(metadata) = std::fs::metadata(path) {
+                file.set_permissions(metadata.permissions())?;
+            }
+            file.write_all(config_str.as_bytes())?;
+            file.sync_all()?;
+            std::fs::rename(&temp_path, path)?;
+            Ok(())
+        })();
+
+        if result.is_err() {
+            let _ = std::fs::remove_file(&temp_path);
+        }
+        result
+    }
+
+    /// Folds entries that name the same token (ignoring case) into the first one: list
+    /// flags are OR-ed, holdings summed at a weighted average price and transaction
+    /// histories combined. Returns how many entries were merged away
+    pub fn merge_duplicate_tokens(&mut self) -> usize {
+        let method = self.cost_basis_method;
+        let before = self.tokens.len();
+        let mut merged: Vec<TokenConfig> = Vec::with_capacity(before);
+
+        for token in self.tokens.drain(..) {
+            match merged.iter_mut().find(|t| t.name.to_lowercase() == token.name.to_lowercase()) {
+                Some(existing) => existing.absorb(token, method),
+                None => merged.push(token),
+            }
+        }
+
+        self.tokens = merged;
+        before - self.tokens.len()
+    }
+
+    /// Sum of the portfolio tokens' target allocations, if any are set. Above 100 the
+    /// targets can't all be met
+    pub fn target_allocation_total(&self) -> Option<f64> {
+        let targets: Vec<f64> = self.tokens.iter()
+            .filter(|token| token.is_in_portfolio())
+            .filter_map(|token| token.target_allocation)
+            .collect();
+        if targets.is_empty() {
+            None
+        } else {
+            Some(targets.iter().sum())
+        }
+    }
+
+    /// The configured HTTPS proxy, or the one from the environment
+    pub fn resolved_https_proxy(&self) -> Option<String> {
+        self.https_proxy.clone()
+            .or_else(|| std::env::var("HTTPS_PROXY").ok())
+            .or_else(|| std::env::var("https_proxy").ok())
+            .filter(|url| !url.trim().is_empty())
+    }
+
+    fn find_token_mut(&mut self, name: &str) -> Option<&mut TokenConfig> {
+        self.tokens.iter_mut().find(|t| t.name.to_lowercase() == name.to_lowercase())
+    }
+
+    /// Adds a token, or turns on the given lists (and updates holdings) for an existing one
+    pub fn add_token(
+        &mut self,
+        name: &str,
+        watchlist: bool,
+        portfolio: bool,
+        owned: Option<f64>,
+        avg_buy_price: Option<f64>,
+    ) -> Result<(), String> {
+        match self.find_token_mut(name) {
+            Some(token) if portfolio && (owned.is_some() || avg_buy_price.is_some())
+                && !token.transactions.is_empty() =>
+            {
+                return Err(format!(
+                    "{} has a transaction history. Use buy/sell to change holdings", name));
+            }
+            Some(token) => {
+                if watchlist {
+                    token.in_watchlist = true;
+                }
+                if portfolio {
+                    token.in_portfolio = true;
+                    if let Some(owned) = owned {
+                        token.owned = Some(owned);
+                    }
+                    if let Some(price) = avg_buy_price {
+                        token.avg_buy_price = Some(price);
+                    }
+                }
+            }
+            None => {
+                self.tokens.push(TokenConfig {
+                    name: name.to_string(),
+                    owned,
+                    avg_buy_price,
+                    in_watchlist: watchlist,
+                    in_portfolio: portfolio,
+                    pinned: false,
+                    transactions: Vec::new(),
+                    target_allocation: None,
+                    staking_apr: None,
+                    tags: Vec::new(),
+                    note: None,
+                    group: None,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Takes a token off the given lists, dropping it entirely once it is on neither
+    pub fn remove_token(&mut self, name: &str, watchlist: bool, portfolio: bool) {
+        if let Some(token) = self.find_token_mut(name) {
+            if watchlist {
+                token.in_watchlist = false;
+            }
+            if portfolio {
+                token.in_portfolio = false;
+                token.owned = None;
+                token.avg_buy_price = None;
+                token.transactions.clear();
+            }
+        }
+
+        self.tokens.retain(|t| t.in_watchlist || t.in_portfolio);
+    }
+
+    /// Overwrites the holdings of a token without a transaction history
+    pub fn set_holdings(&mut self, name: &str, owned: Option<f64>, avg_buy_price: Option<f64>) -> Result<(), String> {
+        match self.find_token_mut(name) {
+            Some(token) if !token.transactions.is_empty() => Err(format!(
+                "{} has a transaction history. Use buy/sell to change holdings", name)),
+            Some(token) => {
+                if let Some(owned) = owned {
+                    token.owned = Some(owned);
+                }
+                if let Some(price) = avg_buy_price {
+                    token.avg_buy_price = Some(price);
+                }
+                Ok(())
+            }
+            None => Err(format!(
+                "Token '{}' not found. Use add <name> -p <amount> <price> to create it", name)),
+        }
+    }
+
+    /// Describes how the tokens in `other` differ from these, one line per token
+    pub fn token_changes(&self, other: &Config) -> Vec<String> {
+        let find = |config: &'_ Config, name: &str| config.tokens.iter()
+            .position(|t| t.name.to_lowercase() == name.to_lowercase());
+        let describe = |value: Option<f64>| value.map_or_else(|| "none".to_string(), |v| v.to_string());
+        let on_off = |flag: bool| if flag { "on" } else { "off" };
+        let mut changes = Vec::new();
+
+        for before in &self.tokens {
+            let after = match find(other, &before.name) {
+                Some(index) => &other.tokens[index],
+                None => {
+                    changes.push(format!("- {} (removed from config)", before.name));
+                    continue;
+                }
+            };
+
+            let mut fields = Vec::new();
+            if before.in_watchlist != after.in_watchlist {
+                fields.push(format!("watchlist {} → {}", on_off(before.in_watchlist), on_off(after.in_watchlist)));
+            }
+            if before.in_portfolio != after.in_portfolio {
+                fields.push(format!("portfolio {} → {}", on_off(before.in_portfolio), on_off(after.in_portfolio)));
+            }
+            if before.owned != after.owned {
+                fields.push(format!("owned {} → {}", describe(before.owned), describe(after.owned)));
+            }
+            if before.avg_buy_price != after.avg_buy_price {
+                fields.push(format!("avg {} → {}", describe(before.avg_buy_price), describe(after.avg_buy_price)));
+            }
+            if before.transactions.len() != after.transactions.len() {
+                fields.push(format!("{} transaction(s) → {}", before.transactions.len(), after.transactions.len()));
+            }
+            if !fields.is_empty() {
+                changes.push(format!("{}: {}", before.name, fields.join(", ")));
+            }
+        }
+
+        for after in other.tokens.iter().filter(|t| find(self, &t.name).is_none()) {
+            let mut lists = Vec::new();
+            if after.in_watchlist {
+                lists.push("watchlist".to_string());
+            }
+            if after.in_portfolio {
+                lists.push(format!("portfolio {} @ {}", describe(after.owned), describe(after.avg_buy_price)));
+            }
+            changes.push(format!("+ {} ({})", after.name, lists.join(", ")));
+        }
+
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A config touching every kind of field: tokens with transactions and tags, optional
+    /// settings, and nested tables
+    fn sample_config() -> Config {
+        serde_json::from_value(serde_json::json!({
+            "provider": "coingecko",
+            "api_key": "test-key",
+            "fear_and_greed_limit": "30",
+            "convert_currency": "EUR",
+            "theme": "light",
+            "number_locale": "de",
+            "bell_on_change_pct": 5.5,
+            "serve_port": 8080,
+            "columns": ["symbol", "price", "change_24h"],
+            "cost_basis_method": "fifo",
+            "tokens": [
+                {"name": "bitcoin", "pinned": true, "target_allocation": 60.0, "staking_apr": 0.0,
+                 "tags": ["long-term", "cold"], "note": "Hardware wallet", "group": "L1",
+                 "transactions": [
+                     {"type": "buy", "quantity": 0.5, "price": 30000.0, "timestamp": 1700000000},
+                     {"type": "sell", "quantity": 0.1, "price": 42000.5, "timestamp": 1710000000}
+                 ],
+                 "owned": 0.4, "avg_buy_price": 30000.0},
+                {"name": "ethereum", "in_portfolio": false},
+                {"name": "solana", "in_watchlist": false, "owned": 12.5, "avg_buy_price": 101.25}
+            ],
+            "alerts": [
+                {"token": "bitcoin", "direction": "above", "price": 75000.0},
+                {"token": "ethereum", "direction": "below", "price": 1800.0, "triggered": true}
+            ],
+            "fear_greed_alert": {"fear": 20, "greed": 80, "notified": "greed"},
+            "paper": {
+                "starting_cash": 5000.0,
+                "cash": 4000.0,
+                "positions": [
+                    {"name": "dogecoin", "transactions": [
+                        {"type": "buy", "quantity": 5000.0, "price": 0.2, "timestamp": 1720000000}
+                    ], "owned": 5000.0, "avg_buy_price": 0.2}
+                ]
+            }
+        }))
+        .expect("sample config should parse")
+    }
+
+    fn round_trip(file_name: &str) {
+        let dir = std::env::temp_dir().join(format!("cointracker-config-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("temp dir should be writable");
+        let path = dir.join(file_name);
+
+        let config = sample_config();
+        config.save(&path).expect("config should save");
+        let loaded = Config::load(&path).expect("saved config should load");
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(
+            serde_json::to_value(&loaded).expect("loaded config should serialize"),
+            serde_json::to_value(&config).expect("sample config should serialize"),
+        );
+        assert!(loaded.validate().is_ok());
+    }
+
+    #[test]
+    fn json_round_trip() {
+        round_trip("config.json");
+    }
+
+    #[test]
+    fn toml_round_trip() {
+        round_trip("config.toml");
+    }
+
+    fn config_with_tokens(cost_basis_method: &str, tokens: serde_json::Value) -> Config {
+        serde_json::from_value(serde_json::json!({
+            "api_key": "test-key",
+            "fear_and_greed_limit": "30",
+            "cost_basis_method": cost_basis_method,
+            "tokens": tokens,
+        }))
+        .expect("config should parse")
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "expected {}, got {}", expected, actual);
+    }
+
+    #[test]
+    fn merge_weights_lots_by_amount() {
+        let mut config = config_with_tokens("average", serde_json::json!([
+            {"name": "bitcoin", "owned": 1.0, "avg_buy_price": 20000.0, "in_portfolio": false,
+             "tags": ["cold"]},
+            {"name": "ethereum"},
+            {"name": "Bitcoin", "owned": 3.0, "avg_buy_price": 40000.0, "pinned": true,
+             "tags": ["COLD", "long-term"]}
+        ]));
+
+        assert_eq!(config.merge_duplicate_tokens(), 1);
+        assert_eq!(config.tokens.len(), 2);
+        let bitcoin = &config.tokens[0];
+        assert_eq!(bitcoin.name, "bitcoin");
+        assert_eq!(bitcoin.owned, Some(4.0));
+        assert_close(bitcoin.avg_buy_price.unwrap(), 35000.0);
+        assert!(bitcoin.in_portfolio && bitcoin.pinned);
+        assert_eq!(bitcoin.tags, ["cold", "long-term"]);
+        assert!(bitcoin.transactions.is_empty());
+    }
+
+    #[test]
+    fn merge_folds_plain_holdings_into_transactions() {
+        let mut config = config_with_tokens("fifo", serde_json::json!([
+            {"name": "solana", "owned": 10.0, "avg_buy_price": 100.0},
+            {"name": "SOLANA", "transactions": [
+                {"type": "sell", "quantity": 3.0, "price": 250.0, "timestamp": 1710000000},
+                {"type": "buy", "quantity": 5.0, "price": 200.0, "timestamp": 1700000000}
+            ], "owned": 2.0, "avg_buy_price": 200.0}
+        ]));
+
+        assert_eq!(config.merge_duplicate_tokens(), 1);
+        let solana = &config.tokens[0];
+        // The plain holdings become the oldest buy, so FIFO sells them first
+        let history: Vec<(TransactionKind, f64, f64, i64)> = solana.transactions.iter()
+            .map(|t| (t.kind, t.quantity, t.price, t.timestamp))
+            .collect();
+        assert_eq!(history, [
+            (TransactionKind::Buy, 10.0, 100.0, 1700000000),
+            (TransactionKind::Buy, 5.0, 200.0, 1700000000),
+            (TransactionKind::Sell, 3.0, 250.0, 1710000000),
+        ]);
+        assert_eq!(solana.owned, Some(12.0));
+        assert_close(solana.avg_buy_price.unwrap(), (7.0 * 100.0 + 5.0 * 200.0) / 12.0);
+        assert_close(solana.cost_basis(CostBasisMethod::Fifo).1, 3.0 * 150.0);
+    }
+
+    fn token_with_history(transactions: serde_json::Value) -> TokenConfig {
+        serde_json::from_value(serde_json::json!({"name": "bitcoin", "transactions": transactions}))
+            .expect("token should parse")
+    }
+
+    #[test]
+    fn fifo_sell_spans_lots_oldest_first() {
+        let token = token_with_history(serde_json::json!([
+            {"type": "buy", "quantity": 2.0, "price": 100.0, "timestamp": 1},
+            {"type": "buy", "quantity": 3.0, "price": 200.0, "timestamp": 2},
+            {"type": "sell", "quantity": 4.0, "price": 250.0, "timestamp": 3}
+        ]));
+
+        let (quantity, avg_price, realized) = token.replay_fifo();
+        // The first lot is used up and two of the second's three units are sold
+        assert_close(quantity, 1.0);
+        assert_close(avg_price, 200.0);
+        assert_close(realized, 2.0 * 150.0 + 2.0 * 50.0);
+    }
+
+    #[test]
+    fn fifo_oversell_only_realizes_held_quantity() {
+        let token = token_with_history(serde_json::json!([
+            {"type": "buy", "quantity": 1.0, "price": 100.0, "timestamp": 1},
+            {"type": "sell", "quantity": 3.0, "price": 200.0, "timestamp": 2},
+            {"type": "buy", "quantity": 2.0, "price": 300.0, "timestamp": 3}
+        ]));
+
+        let (quantity, avg_price, realized) = token.replay_fifo();
+        // The unmatched units aren't held against the later buy
+        assert_close(quantity, 2.0);
+        assert_close(avg_price, 300.0);
+        assert_close(realized, 100.0);
+    }
+
+    #[test]
+    fn fifo_realized_loss_is_negative() {
+        let token = token_with_history(serde_json::json!([
+            {"type": "buy", "quantity": 1.0, "price": 100.0, "timestamp": 1},
+            {"type": "buy", "quantity": 1.0, "price": 20.0, "timestamp": 2},
+            {"type": "sell", "quantity": 1.0, "price": 80.0, "timestamp": 3}
+        ]));
+
+        // Matched against the older, dearer lot, so it's a loss even though the sell
+        // price is above the average
+        assert_close(token.cost_basis(CostBasisMethod::Fifo).1, -20.0);
+        assert_close(token.cost_basis(CostBasisMethod::Average).1, 20.0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn save_keeps_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("cointracker-config-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("temp dir should be writable");
+        let mode = |path: &Path| std::fs::metadata(path).expect("config should exist").permissions().mode() & 0o777;
+
+        let new_path = dir.join("new.json");
+        let _ = std::fs::remove_file(&new_path);
+        sample_config().save(&new_path).expect("config should save");
+        assert_eq!(mode(&new_path), 0o600);
+
+        let existing_path = dir.join("existing.json");
+        std::fs::write(&existing_path, "{}").expect("config should be writable");
+        std::fs::set_permissions(&existing_path, std::fs::Permissions::from_mode(0o640)).expect("chmod should work");
+        sample_config().save(&existing_path).expect("config should save");
+        assert_eq!(mode(&existing_path), 0o640);
+
+        let _ = std::fs::remove_file(&new_path);
+        let _ = std::fs::remove_file(&existing_path);
+    }
 }
@@ -1,20 +1,32 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 #[derive(Debug, Deserialize)]
 pub struct CMCResponse {
     pub status: Status,
+    /// Absent from error bodies, which only carry the status
+    #[serde(default)]
     pub data: HashMap<String, CryptoData>,
 }
 
+/// Coins ranked by market cap, from the listings endpoint
 #[derive(Debug, Deserialize)]
+pub struct ListingsResponse {
+    pub status: Status,
+    pub data: Vec<CryptoData>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct CryptoData {
+    pub id: u64,
     pub name: String,
+    #[serde(default)]
+    pub slug: Option<String>,
     pub symbol: String,
     pub quote: HashMap<String, Quote>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Quote {
     pub price: f64,
     pub volume_24h: Option<f64>,
@@ -25,6 +37,12 @@ pub struct Quote {
     pub percent_change_30d: Option<f64>,
     pub percent_change_90d: Option<f64>,
     pub market_cap: Option<f64>,
+    /// Intraday range. CoinMarketCap's quotes endpoint doesn't report it, so for CMC
+    /// it's filled in from the current day's OHLCV candle
+    #[serde(default)]
+    pub high_24h: Option<f64>,
+    #[serde(default)]
+    pub low_24h: Option<f64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -32,3 +50,44 @@ pub struct Status {
     pub error_code: i32,
     pub error_message: Option<String>,
 }
+
+/// The current (UTC) day's candle of each coin, keyed by CMC id
+#[derive(Debug, Deserialize)]
+pub struct OhlcvLatestResponse {
+    pub status: Status,
+    #[serde(default)]
+    pub data: HashMap<String, OhlcvLatest>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OhlcvLatest {
+    pub quote: HashMap<String, DailyRange>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DailyRange {
+    pub high: Option<f64>,
+    pub low: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OhlcvResponse {
+    pub status: Status,
+    pub data: HashMap<String, OhlcvData>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OhlcvData {
+    pub quotes: Vec<OhlcvQuote>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OhlcvQuote {
+    pub time_open: String,
+    pub quote: HashMap<String, OhlcvValues>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OhlcvValues {
+    pub close: f64,
+}
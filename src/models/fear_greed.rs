@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
 
 #[derive(Debug, Deserialize)]
@@ -20,3 +21,12 @@ pub struct FearGreedData {
     pub value: u64,
     pub value_classification: String,
 }
+
+impl FearGreedData {
+    /// The day this point refers to, or None when the timestamp is empty, malformed or
+    /// not after the Unix epoch
+    pub fn date(&self) -> Option<DateTime<Utc>> {
+        let secs = self.timestamp.trim().parse::<i64>().ok().filter(|secs| *secs > 0)?;
+        DateTime::from_timestamp(secs, 0)
+    }
+}
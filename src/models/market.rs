@@ -0,0 +1,26 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::models::crypto::Status;
+
+#[derive(Debug, Deserialize)]
+pub struct GlobalMetricsResponse {
+    pub status: Status,
+    pub data: GlobalMetrics,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GlobalMetrics {
+    pub active_cryptocurrencies: u64,
+    pub btc_dominance: f64,
+    pub eth_dominance: Option<f64>,
+    pub quote: HashMap<String, GlobalQuote>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GlobalQuote {
+    pub total_market_cap: f64,
+    pub total_volume_24h: f64,
+    pub total_market_cap_yesterday_percentage_change: Option<f64>,
+    pub total_volume_24h_yesterday_percentage_change: Option<f64>,
+}
@@ -1,3 +1,5 @@
 pub mod config;
 pub mod crypto;
 pub mod fear_greed;
+pub mod market;
+pub mod portfolio;
@@ -0,0 +1,236 @@
+use serde::Serialize;
+
+use crate::models::config::{CostBasisMethod, TokenConfig};
+use crate::models::crypto::{CryptoData, Quote};
+
+/// A portfolio holding joined with its current quote, along with the derived
+/// figures shown in the portfolio table. Holdings without a quote in the configured
+/// currency have no market value: their value and P/L figures are zero and they
+/// are left out of the totals
+#[derive(Debug)]
+pub struct Position<'a> {
+    pub crypto: &'a CryptoData,
+    pub quote: Option<&'a Quote>,
+    pub holdings: f64,
+    pub avg_buy_price: f64,
+    pub current_value: f64,
+    pub cost_basis: f64,
+    pub profit_loss: f64,
+    pub profit_loss_pct: f64,
+    pub realized_profit_loss: f64,
+    /// Price at which current value equals cost basis
+    pub break_even_price: f64,
+    /// How far the price has to move to reach break-even, relative to the current price
+    pub to_break_even_pct: Option<f64>,
+    /// Desired share of the portfolio value, in percent
+    pub target_allocation: Option<f64>,
+    /// Current value times the staking APR, for priced positions with an APR
+    pub annual_yield: Option<f64>,
+    pub tags: &'a [String],
+    pub note: Option<&'a str>,
+}
+
+impl<'a> Position<'a> {
+    pub fn new(
+        token: &'a TokenConfig,
+        crypto: &'a CryptoData,
+        quote: Option<&'a Quote>,
+        method: CostBasisMethod,
+    ) -> Self {
+        let holdings = token.owned.unwrap_or(0.0);
+        let (avg_buy_price, realized_profit_loss) = token.cost_basis(method);
+        let cost_basis = holdings * avg_buy_price;
+        let (current_value, profit_loss) = match quote {
+            Some(quote) => {
+                let current_value = holdings * quote.price;
+                (current_value, current_value - cost_basis)
+            }
+            None => (0.0, 0.0),
+        };
+        let profit_loss_pct = if quote.is_some() && cost_basis > 0.0 {
+            (profit_loss / cost_basis) * 100.0
+        } else {
+            0.0
+        };
+
+        let break_even_price = if holdings > 0.0 { cost_basis / holdings } else { 0.0 };
+        let to_break_even_pct = if quote.is_some() && current_value > 0.0 {
+            Some((cost_basis - current_value) / current_value * 100.0)
+        } else {
+            None
+        };
+
+        Position {
+            crypto,
+            quote,
+            holdings,
+            avg_buy_price,
+            current_value,
+            cost_basis,
+            profit_loss,
+            profit_loss_pct,
+            realized_profit_loss,
+            break_even_price,
+            to_break_even_pct,
+            target_allocation: token.target_allocation,
+            annual_yield: token.staking_apr
+                .filter(|_| quote.is_some())
+                .map(|apr| current_value * apr / 100.0),
+            tags: &token.tags,
+            note: token.note.as_deref(),
+        }
+    }
+}
+
+/// Aggregate value and profit/loss across a set of positions
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct PortfolioTotals {
+    pub value: f64,
+    pub cost: f64,
+    pub profit_loss: f64,
+    pub profit_loss_pct: f64,
+    pub realized_profit_loss: f64,
+    /// Projected yearly staking yield across the positions with an APR
+    pub annual_yield: f64,
+}
+
+impl PortfolioTotals {
+    /// Sums the priced positions; unpriced holdings are skipped entirely except for
+    /// realized P/L, which doesn't depend on a current quote
+    pub fn from_positions(positions: &[Position]) -> Self {
+        let priced = || positions.iter().filter(|p| p.quote.is_some());
+        let value: f64 = priced().map(|p| p.current_value).sum();
+        let cost: f64 = priced().map(|p| p.cost_basis).sum();
+        let profit_loss = value - cost;
+        let profit_loss_pct = if cost > 0.0 {
+            (profit_loss / cost) * 100.0
+        } else {
+            0.0
+        };
+        let realized_profit_loss = positions.iter().map(|p| p.realized_profit_loss).sum();
+        // Folded from 0.0 because an empty f64 sum is -0.0, which would print as "-$0.00"
+        let annual_yield = positions.iter().filter_map(|p| p.annual_yield).fold(0.0, |sum, y| sum + y);
+
+        PortfolioTotals {
+            value,
+            cost,
+            profit_loss,
+            profit_loss_pct,
+            realized_profit_loss,
+            annual_yield,
+        }
+    }
+
+    /// Change in value over a window, from each position's value at its start, and that
+    /// change as a percentage of the starting value. `percent_change` picks the window's
+    /// figure from a quote
+    pub fn window_change(&self, positions: &[Position], percent_change: impl Fn(&Quote) -> Option<f64>) -> (f64, f64) {
+        let change: f64 = positions.iter()
+            .filter_map(|position| {
+                let pct = position.quote
+                    .and_then(&percent_change)
+                    .filter(|pct| *pct > -100.0)?;
+                Some(position.current_value - position.current_value / (1.0 + pct / 100.0))
+            })
+            .sum();
+
+        let start_value = self.value - change;
+        let change_pct = if start_value > 0.0 {
+            (change / start_value) * 100.0
+        } else {
+            0.0
+        };
+        (change, change_pct)
+    }
+
+    /// Share of the total portfolio value held in `position`, in percent
+    pub fn allocation_pct(&self, position: &Position) -> Option<f64> {
+        if position.quote.is_some() && self.value > 0.0 {
+            Some(position.current_value / self.value * 100.0)
+        } else {
+            None
+        }
+    }
+
+    /// Amount to buy (positive) or sell (negative) to bring `position` to its target
+    /// allocation at current prices
+    pub fn rebalance_amount(&self, position: &Position) -> Option<f64> {
+        let target = position.target_allocation?;
+        if position.quote.is_none() || self.value <= 0.0 {
+            return None;
+        }
+        Some(target / 100.0 * self.value - position.current_value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn token(owned: f64, avg_buy_price: f64) -> TokenConfig {
+        serde_json::from_value(serde_json::json!({
+            "name": "bitcoin",
+            "owned": owned,
+            "avg_buy_price": avg_buy_price,
+        }))
+        .expect("token should parse")
+    }
+
+    fn crypto(price: f64, change_24h: f64) -> CryptoData {
+        CryptoData {
+            id: 1,
+            name: "Bitcoin".to_string(),
+            slug: Some("bitcoin".to_string()),
+            symbol: "BTC".to_string(),
+            quote: HashMap::from([("USD".to_string(), Quote {
+                price,
+                volume_24h: None,
+                volume_change_24h: None,
+                percent_change_1h: None,
+                percent_change_24h: Some(change_24h),
+                percent_change_7d: None,
+                percent_change_30d: None,
+                percent_change_90d: None,
+                market_cap: None,
+                high_24h: None,
+                low_24h: None,
+            })]),
+        }
+    }
+
+    #[test]
+    fn empty_portfolio_has_zero_percentages() {
+        let totals = PortfolioTotals::from_positions(&[]);
+        let (change, change_pct) = totals.window_change(&[], |quote| quote.percent_change_24h);
+        assert_eq!(totals.profit_loss_pct, 0.0);
+        assert_eq!(change, 0.0);
+        assert_eq!(change_pct, 0.0);
+    }
+
+    #[test]
+    fn zero_cost_positions_have_zero_percentages() {
+        let free = token(2.0, 0.0);
+        let empty = token(0.0, 0.0);
+        let btc = crypto(50_000.0, 5.0);
+        let quote = btc.quote.get("USD");
+        let positions = [
+            Position::new(&free, &btc, quote, CostBasisMethod::Average),
+            Position::new(&empty, &btc, quote, CostBasisMethod::Average),
+        ];
+        assert_eq!(positions[0].profit_loss_pct, 0.0);
+        assert_eq!(positions[1].profit_loss_pct, 0.0);
+
+        let totals = PortfolioTotals::from_positions(&positions);
+        assert_eq!(totals.cost, 0.0);
+        assert_eq!(totals.profit_loss_pct, 0.0);
+        let (_, change_pct) = totals.window_change(&positions, |quote| quote.percent_change_24h);
+        assert!(change_pct.is_finite());
+
+        let worthless = crypto(0.0, 5.0);
+        let positions = [Position::new(&empty, &worthless, worthless.quote.get("USD"), CostBasisMethod::Average)];
+        let totals = PortfolioTotals::from_positions(&positions);
+        assert_eq!(totals.profit_loss_pct, 0.0);
+        assert_eq!(totals.window_change(&positions, |quote| quote.percent_change_24h).1, 0.0);
+    }
+}
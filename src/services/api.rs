@@ -1,53 +1,403 @@
-use anyhow::Result;
-use crate::models::crypto::{CMCResponse, CryptoData};
+use anyhow::{Context, Result};
+use crate::models::crypto::{CMCResponse, CryptoData, ListingsResponse, OhlcvLatestResponse, OhlcvResponse, OhlcvQuote};
 use crate::models::fear_greed::{FearGreedResponse, FearGreedData};
+use crate::models::market::{GlobalMetricsResponse, GlobalMetrics};
 use std::collections::HashMap;
 use itertools::Itertools;
-use crate::services::logger::{log_error, log_info};
+use futures::future::join_all;
+use crate::services::logger::{log_debug, log_error, log_info};
+use rand::Rng;
+use reqwest::StatusCode;
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
 
 const CMC_QUOTES_URL: &str = "https://pro-api.coinmarketcap.com/v2/cryptocurrency/quotes/latest";
 const CMC_FEAR_GREED_URL: &str = "https://pro-api.coinmarketcap.com/v3/fear-and-greed/historical";
+const CMC_OHLCV_URL: &str = "https://pro-api.coinmarketcap.com/v2/cryptocurrency/ohlcv/historical";
+const CMC_OHLCV_LATEST_URL: &str = "https://pro-api.coinmarketcap.com/v2/cryptocurrency/ohlcv/latest";
+const CMC_GLOBAL_METRICS_URL: &str = "https://pro-api.coinmarketcap.com/v1/global-metrics/quotes/latest";
+const CMC_LISTINGS_URL: &str = "https://pro-api.coinmarketcap.com/v1/cryptocurrency/listings/latest";
 
-/// Fetches current cryptocurrency prices from CoinMarketCap API
-pub async fn fetch_prices(api_key: &str, token_names: &[String]) -> Result<HashMap<String, CryptoData>> {
-    let client = reqwest::Client::new();
-    let slugs = token_names.iter()
-        .map(|token| token.as_str())
-        .join(",");
-    
+/// CMC status codes for a missing, invalid, deactivated or disabled API key
+const CMC_INVALID_KEY_CODES: [i32; 4] = [1001, 1002, 1003, 1007];
+/// CMC status codes for exceeding the minute, daily, monthly or IP rate limit
+const CMC_RATE_LIMIT_CODES: [i32; 4] = [1008, 1009, 1010, 1011];
+/// Usage headers on CMC responses: credits left in the plan's current period, and its total
+const CMC_CREDITS_LEFT_HEADER: &str = "x-ratelimit-remaining";
+const CMC_CREDIT_LIMIT_HEADER: &str = "x-ratelimit-limit";
+
+/// Everything that can go wrong talking to a price API, split by what the caller
+/// should do about it
+#[derive(Debug)]
+pub enum ApiError {
+    /// No usable response: connection failure, timeout or an unreachable proxy
+    Network(reqwest::Error),
+    /// Too many requests; worth trying again after a pause
+    RateLimited(String),
+    /// The key was rejected; retrying can't succeed until the config changes
+    Auth(String),
+    /// The response didn't have the expected shape
+    Parse(String),
+    /// Any other error status returned by the API
+    Api { code: i32, message: String },
+}
+
+pub type ApiResult<T> = std::result::Result<T, ApiError>;
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiError::Network(e) if e.is_timeout() => write!(f, "Request timed out"),
+            ApiError::Network(e) => write!(f, "Network error: {}", e),
+            ApiError::RateLimited(message) => write!(f, "Rate limited: {}", message),
+            ApiError::Auth(message) => write!(f, "Invalid API key: {}", message),
+            ApiError::Parse(message) => write!(f, "{}", message),
+            ApiError::Api { code, message } => write!(f, "API Error {}: {}", code, message),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl From<reqwest::Error> for ApiError {
+    fn from(error: reqwest::Error) -> Self {
+        ApiError::Network(error)
+    }
+}
+
+impl ApiError {
+    /// Classifies an error code from a CMC response body
+    fn from_status(code: i32, message: String) -> Self {
+        if CMC_INVALID_KEY_CODES.contains(&code) {
+            ApiError::Auth(message)
+        } else if CMC_RATE_LIMIT_CODES.contains(&code) {
+            ApiError::RateLimited(message)
+        } else {
+            ApiError::Api { code, message }
+        }
+    }
+
+    /// Classifies an unsuccessful HTTP status whose body didn't explain itself
+    pub(crate) fn from_http(status: StatusCode) -> Self {
+        let message = format!("HTTP {}", status);
+        match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => ApiError::Auth(message),
+            StatusCode::TOO_MANY_REQUESTS => ApiError::RateLimited(message),
+            _ => ApiError::Api { code: status.as_u16() as i32, message },
+        }
+    }
+
+    /// A response body that failed to deserialize, logged under `category`
+    pub(crate) fn parse(category: &str, what: &str, error: serde_json::Error) -> Self {
+        log_error(category, &error.to_string()).unwrap_or(());
+        ApiError::Parse(format!("Failed to parse {}: {}", what, error))
+    }
+}
+
+/// The API error at the root of `error`, if it came from the API layer
+pub fn api_error(error: &anyhow::Error) -> Option<&ApiError> {
+    error.downcast_ref::<ApiError>()
+}
+
+/// Largest `limit` the listings endpoint accepts
+pub const MAX_LISTINGS_LIMIT: u32 = 5000;
+
+/// Largest `limit` the fear & greed historical endpoint accepts
+pub const MAX_FEAR_GREED_LIMIT: u32 = 500;
+
+static CLIENT: RwLock<Option<reqwest::Client>> = RwLock::new(None);
+static PROXY: RwLock<Option<String>> = RwLock::new(None);
+static CREDITS: Mutex<Option<Credits>> = Mutex::new(None);
+
+/// API credit usage from the usage headers of the latest CoinMarketCap response
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Credits {
+    pub remaining: u64,
+    pub limit: Option<u64>,
+}
+
+/// Remembers the credit usage a response reports, if it has the usage headers. Called
+/// before the body is read, since that consumes the response
+fn record_credits(headers: &reqwest::header::HeaderMap) {
+    let number = |name: &str| headers.get(name)?.to_str().ok()?.trim().parse::<u64>().ok();
+    if let Some(remaining) = number(CMC_CREDITS_LEFT_HEADER) {
+        if let Ok(mut credits) = CREDITS.lock() {
+            *credits = Some(Credits { remaining, limit: number(CMC_CREDIT_LIMIT_HEADER) });
+        }
+    }
+}
+
+/// Credit usage reported by the most recent CoinMarketCap response that had it
+pub fn latest_credits() -> Option<Credits> {
+    CREDITS.lock().ok().and_then(|credits| *credits)
+}
+
+/// Builds the HTTP client shared by every request, with the given per-request timeout
+/// and optional HTTPS proxy. Calling it again (on `reload`) swaps the client for the
+/// requests that follow; ones already in flight finish on the old client
+pub fn init_client(timeout_secs: u64, https_proxy: Option<&str>) -> Result<()> {
+    let mut builder = reqwest::Client::builder()
+        .timeout(Duration::from_secs(timeout_secs));
+    if let Some(url) = https_proxy {
+        let proxy = reqwest::Proxy::https(url)
+            .with_context(|| format!("Invalid HTTPS proxy URL '{}'", url))?;
+        builder = builder.proxy(proxy);
+    }
+    let client = builder.build()?;
+    if let (Ok(mut current), Ok(mut proxy)) = (CLIENT.write(), PROXY.write()) {
+        *current = Some(client);
+        *proxy = https_proxy.map(str::to_string);
+    }
+    Ok(())
+}
+
+/// Describes a fetch error for the UI. With a proxy configured, failing to connect
+/// usually means the proxy is unreachable, so say which one
+pub fn describe_error(error: &anyhow::Error) -> String {
+    let proxy = PROXY.read().ok().and_then(|proxy| proxy.clone());
+    match (api_error(error), proxy) {
+        (Some(ApiError::Network(e)), Some(proxy)) if e.is_connect() => {
+            format!("Could not connect through proxy {}: {}", proxy, error)
+        }
+        _ => error.to_string(),
+    }
+}
+
+/// The shared HTTP client, so connections are pooled across refreshes. Cloning it is
+/// cheap and shares the pool
+pub fn http_client() -> reqwest::Client {
+    if let Some(client) = CLIENT.read().ok().and_then(|client| client.clone()) {
+        return client;
+    }
+    let mut current = CLIENT.write().unwrap_or_else(|e| e.into_inner());
+    current.get_or_insert_with(reqwest::Client::new).clone()
+}
+
+/// Prices gathered from one or more batched quote requests
+pub struct PriceFetch {
+    pub data: HashMap<String, CryptoData>,
+    /// Errors from batches that failed while at least one other batch succeeded
+    pub warnings: Vec<String>,
+    /// Requested tokens a successful batch came back without, usually misspelled slugs
+    pub missing: Vec<String>,
+}
+
+/// The requested tokens that none of the returned coins has as its slug
+pub fn missing_tokens(requested: &[String], data: &HashMap<String, CryptoData>) -> Vec<String> {
+    requested.iter()
+        .filter(|token| !data.values().any(|crypto| {
+            crypto.slug.as_deref().is_some_and(|slug| slug.eq_ignore_ascii_case(token))
+        }))
+        .cloned()
+        .collect()
+}
+
+/// Fetches current cryptocurrency prices from CoinMarketCap API, quoted in `convert`.
+/// Tokens are split into batches of `batch_size` slugs that are requested concurrently;
+/// the call only fails if every batch fails.
+pub async fn fetch_prices(
+    api_key: &str,
+    token_names: &[String],
+    convert: &str,
+    batch_size: usize,
+    max_retries: u32,
+    retry_base_ms: u64,
+) -> ApiResult<PriceFetch> {
+    let client = &http_client();
+
+    let batches = token_names.chunks(batch_size.max(1)).collect::<Vec<_>>();
+    let slugs = batches.iter()
+        .map(|batch| batch.iter().map(|token| token.as_str()).join(","))
+        .collect::<Vec<_>>();
+    let results = join_all(slugs.iter().map(|slugs| {
+        fetch_price_batch(client, api_key, slugs, convert, max_retries, retry_base_ms)
+    })).await;
+
+    // CMC leaves unknown slugs out of `data` without an error
+    let mut data = HashMap::new();
+    let mut errors = Vec::new();
+    let mut missing = Vec::new();
+    for (batch, result) in batches.iter().zip(results) {
+        match result {
+            Ok(found) => {
+                missing.extend(missing_tokens(batch, &found));
+                data.extend(found);
+            }
+            Err(e) => errors.push(e),
+        }
+    }
+
+    if data.is_empty() && !errors.is_empty() {
+        return Err(errors.remove(0));
+    }
+
+    if !errors.is_empty() {
+        log_error("Price Fetch", &format!("{} of {} batches failed", errors.len(), batches.len())).unwrap_or(());
+    }
+
+    Ok(PriceFetch {
+        data,
+        warnings: errors.iter().map(|e| e.to_string()).collect(),
+        missing,
+    })
+}
+
+/// Fetches quotes for one comma-separated batch of slugs
+async fn fetch_price_batch(
+    client: &reqwest::Client,
+    api_key: &str,
+    slugs: &str,
+    convert: &str,
+    max_retries: u32,
+    retry_base_ms: u64,
+) -> ApiResult<HashMap<String, CryptoData>> {
+    let response = send_with_retry(
+        || client
+            .get(CMC_QUOTES_URL)
+            .header("X-CMC_PRO_API_KEY", api_key)
+            .query(&[
+                ("slug", slugs),
+                ("convert", convert),
+            ]),
+        max_retries,
+        retry_base_ms,
+    ).await?;
+
+    let http_status = response.status();
+    record_credits(response.headers());
+    let response_text = response.text().await?;
+    let mut data = parse_quotes_response(http_status, &response_text)?;
+
+    // Quotes carry no intraday range, so it comes from today's candle. Plans without
+    // OHLCV access just leave the range unknown
+    if !data.is_empty() {
+        let ids = data.keys().join(",");
+        match fetch_daily_ranges(client, api_key, &ids, convert).await {
+            Ok(ranges) => apply_daily_ranges(&mut data, ranges, convert),
+            Err(e) => log_debug("Daily Range", &e.to_string()).unwrap_or(()),
+        }
+    }
+    Ok(data)
+}
+
+/// Today's OHLCV candle for a comma-separated batch of CMC ids
+async fn fetch_daily_ranges(
+    client: &reqwest::Client,
+    api_key: &str,
+    ids: &str,
+    convert: &str,
+) -> ApiResult<OhlcvLatestResponse> {
     let response = client
-        .get(CMC_QUOTES_URL)
+        .get(CMC_OHLCV_LATEST_URL)
         .header("X-CMC_PRO_API_KEY", api_key)
-        .query(&[
-            ("slug", slugs.as_str()),
-            ("convert", "USD"),
-        ])
+        .query(&[("id", ids), ("convert", convert)])
         .send()
         .await?;
 
+    let http_status = response.status();
+    record_credits(response.headers());
     let response_text = response.text().await?;
-    
-    match serde_json::from_str::<CMCResponse>(&response_text) {
+    parse_daily_ranges_response(http_status, &response_text)
+}
+
+/// Parses an OHLCV latest response body, like [`parse_quotes_response`]
+pub fn parse_daily_ranges_response(http_status: StatusCode, response_text: &str) -> ApiResult<OhlcvLatestResponse> {
+    match serde_json::from_str::<OhlcvLatestResponse>(response_text) {
+        Ok(parsed) if parsed.status.error_code != 0 => Err(ApiError::from_status(
+            parsed.status.error_code, parsed.status.error_message.unwrap_or_default())),
+        Ok(parsed) => Ok(parsed),
+        Err(_) if !http_status.is_success() => Err(ApiError::from_http(http_status)),
+        Err(e) => Err(ApiError::parse("Daily Range Parse Error", "OHLCV latest response", e)),
+    }
+}
+
+/// Copies each coin's high and low for the day into its `convert` quote
+fn apply_daily_ranges(data: &mut HashMap<String, CryptoData>, ranges: OhlcvLatestResponse, convert: &str) {
+    for (id, candle) in ranges.data {
+        let quote = data.get_mut(&id).and_then(|crypto| crypto.quote.get_mut(convert));
+        if let (Some(quote), Some(range)) = (quote, candle.quote.get(convert)) {
+            quote.high_24h = range.high;
+            quote.low_24h = range.low;
+        }
+    }
+}
+
+/// Turns a quotes response body into coins keyed by CMC id. Kept free of I/O so recorded
+/// responses can be fed through it directly
+pub fn parse_quotes_response(http_status: StatusCode, response_text: &str) -> ApiResult<HashMap<String, CryptoData>> {
+    match serde_json::from_str::<CMCResponse>(response_text) {
         Ok(parsed) => {
             if parsed.status.error_code != 0 {
                 let error_msg = parsed.status.error_message.unwrap_or_default();
-                log_error("API Error", &error_msg)?;
-                anyhow::bail!("API Error: {}", error_msg);
+                log_error("API Error", &error_msg).unwrap_or(());
+                return Err(ApiError::from_status(parsed.status.error_code, error_msg));
             }
             Ok(parsed.data)
         },
-        Err(e) => {
-            log_error("Parse Error", &e.to_string())?;
-            anyhow::bail!("Failed to parse API response: {}", e)
+        // Gateway errors (a rate limit hit before CMC itself answers) aren't JSON at all
+        Err(_) if !http_status.is_success() => {
+            log_error("API Error", &format!("HTTP {}", http_status)).unwrap_or(());
+            Err(ApiError::from_http(http_status))
         }
+        Err(e) => Err(ApiError::parse("Parse Error", "API response", e)),
     }
 }
 
+/// Sends the request built by `request`. Transient failures (connection errors, 5xx
+/// and 429 responses) are retried up to `max_retries` times with exponential backoff;
+/// anything else is returned immediately
+pub async fn send_with_retry(
+    request: impl Fn() -> reqwest::RequestBuilder,
+    max_retries: u32,
+    retry_base_ms: u64,
+) -> ApiResult<reqwest::Response> {
+    let mut attempt = 0;
+    loop {
+        let result = request().send().await;
+
+        let retryable = match &result {
+            Ok(response) => {
+                let status = response.status();
+                status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+            }
+            Err(e) => e.is_connect() || e.is_timeout() || e.is_request(),
+        };
+
+        if !retryable || attempt >= max_retries {
+            return match result {
+                Ok(response) if response.status() == StatusCode::TOO_MANY_REQUESTS => {
+                    Err(ApiError::from_http(response.status()))
+                }
+                result => Ok(result?),
+            };
+        }
+
+        let delay = backoff_delay(retry_base_ms, attempt);
+        let reason = match &result {
+            Ok(response) => format!("HTTP {}", response.status()),
+            Err(e) => e.to_string(),
+        };
+        log_info("Price Fetch", &format!(
+            "Attempt {} failed ({}), retrying in {}ms",
+            attempt + 1, reason, delay.as_millis())).unwrap_or(());
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// Exponential backoff for the given (zero-based) retry attempt, with up to one
+/// base interval of random jitter so concurrent clients don't retry in lockstep
+fn backoff_delay(base_ms: u64, attempt: u32) -> Duration {
+    let exponential = base_ms.saturating_mul(1u64 << attempt.min(16));
+    let jitter = rand::thread_rng().gen_range(0..=base_ms);
+    Duration::from_millis(exponential.saturating_add(jitter))
+}
+
 /// Fetches historical fear and greed index data from CoinMarketCap API
-pub async fn fetch_fear_greed(api_key: &str, limit: &str) -> Result<Vec<FearGreedData>> {
-    let client = reqwest::Client::new();
+pub async fn fetch_fear_greed(api_key: &str, limit: &str) -> ApiResult<Vec<FearGreedData>> {
+    let client = &http_client();
     
-    log_info("Fear & Greed", "Fetching historical data...")?;
+    log_debug("Fear & Greed", "Fetching historical data...").unwrap_or(());
     
     let response = client
         .get(CMC_FEAR_GREED_URL)
@@ -58,52 +408,314 @@ pub async fn fetch_fear_greed(api_key: &str, limit: &str) -> Result<Vec<FearGree
         .send()
         .await?;
 
+    let http_status = response.status();
+    record_credits(response.headers());
     let response_text = response.text().await?;
     
     // Don't log the full response, just log the status
-    log_info("Fear & Greed", "Response received successfully")?;
-    
-    match serde_json::from_str::<FearGreedResponse>(&response_text) {
+    log_debug("Fear & Greed", "Response received successfully").unwrap_or(());
+    parse_fear_greed_response(http_status, &response_text)
+}
+
+/// Turns a fear & greed response body into data points, newest first. Like
+/// [`parse_quotes_response`] it does no I/O beyond logging
+pub fn parse_fear_greed_response(http_status: StatusCode, response_text: &str) -> ApiResult<Vec<FearGreedData>> {
+    match serde_json::from_str::<FearGreedResponse>(response_text) {
         Ok(parsed) => {
             if parsed.status.error_code_str != "0" {
                 // Keep this as error since it's an actual API error
-                log_error("Fear & Greed API Error", &parsed.status.error_message)?;
-                anyhow::bail!("API Error: {}", parsed.status.error_message);
+                log_error("Fear & Greed API Error", &parsed.status.error_message).unwrap_or(());
+                let code = parsed.status.error_code_str.parse().unwrap_or(-1);
+                return Err(ApiError::from_status(code, parsed.status.error_message));
             }
             
             // Log data points as INFO
             if let Some(first) = parsed.data.first() {
-                let ts = first.timestamp.parse::<i64>().unwrap_or(0);
-                let date = chrono::DateTime::from_timestamp(ts, 0)
-                    .unwrap_or_default()
-                    .format("%Y-%m-%d %H:%M:%S")
-                    .to_string();
+                let date = first.date()
+                    .map(|date| date.format("%Y-%m-%d %H:%M:%S").to_string())
+                    .unwrap_or_else(|| format!("invalid timestamp '{}'", first.timestamp));
                 log_info("Fear & Greed", 
                     &format!("Latest data point: {} = {} ({})", 
-                        date, first.value, first.value_classification))?;
+                        date, first.value, first.value_classification)).unwrap_or(());
             }
             
             if let Some(last) = parsed.data.last() {
-                let ts = last.timestamp.parse::<i64>().unwrap_or(0);
-                let date = chrono::DateTime::from_timestamp(ts, 0)
-                    .unwrap_or_default()
-                    .format("%Y-%m-%d %H:%M:%S")
-                    .to_string();
+                let date = last.date()
+                    .map(|date| date.format("%Y-%m-%d %H:%M:%S").to_string())
+                    .unwrap_or_else(|| format!("invalid timestamp '{}'", last.timestamp));
                 log_info("Fear & Greed", 
                     &format!("Oldest data point: {} = {} ({})", 
-                        date, last.value, last.value_classification))?;
+                        date, last.value, last.value_classification)).unwrap_or(());
             }
 
             // Add a summary log
             log_info("Fear & Greed", 
-                &format!("Successfully fetched {} data points", parsed.data.len()))?;
+                &format!("Successfully fetched {} data points", parsed.data.len())).unwrap_or(());
             
             Ok(parsed.data)
         },
-        Err(e) => {
-            // Keep this as error since it's a parsing error
-            log_error("Fear & Greed Parse Error", &e.to_string())?;
-            anyhow::bail!("Failed to parse Fear & Greed response: {}", e)
+        Err(_) if !http_status.is_success() => Err(ApiError::from_http(http_status)),
+        Err(e) => Err(ApiError::parse("Fear & Greed Parse Error", "Fear & Greed response", e)),
+    }
+}
+
+/// Fetches global market metrics (total market cap, volume, dominance) from CoinMarketCap API
+pub async fn fetch_global_metrics(api_key: &str, convert: &str) -> ApiResult<GlobalMetrics> {
+    let client = &http_client();
+
+    let response = client
+        .get(CMC_GLOBAL_METRICS_URL)
+        .header("X-CMC_PRO_API_KEY", api_key)
+        .query(&[
+            ("convert", convert),
+        ])
+        .send()
+        .await?;
+
+    let http_status = response.status();
+    record_credits(response.headers());
+    let response_text = response.text().await?;
+
+    match serde_json::from_str::<GlobalMetricsResponse>(&response_text) {
+        Ok(parsed) => {
+            if parsed.status.error_code != 0 {
+                let error_msg = parsed.status.error_message.unwrap_or_default();
+                log_error("Global Metrics API Error", &error_msg).unwrap_or(());
+                return Err(ApiError::from_status(parsed.status.error_code, error_msg));
+            }
+            Ok(parsed.data)
+        },
+        Err(_) if !http_status.is_success() => Err(ApiError::from_http(http_status)),
+        Err(e) => Err(ApiError::parse("Global Metrics Parse Error", "global metrics response", e)),
+    }
+}
+
+/// Fetches the `limit` largest coins by market cap from CoinMarketCap API
+pub async fn fetch_listings(api_key: &str, limit: u32, convert: &str) -> ApiResult<Vec<CryptoData>> {
+    let client = &http_client();
+    let limit = limit.min(MAX_LISTINGS_LIMIT).to_string();
+
+    let response = client
+        .get(CMC_LISTINGS_URL)
+        .header("X-CMC_PRO_API_KEY", api_key)
+        .query(&[
+            ("limit", limit.as_str()),
+            ("sort", "market_cap"),
+            ("convert", convert),
+        ])
+        .send()
+        .await?;
+
+    let http_status = response.status();
+    record_credits(response.headers());
+    let response_text = response.text().await?;
+
+    match serde_json::from_str::<ListingsResponse>(&response_text) {
+        Ok(parsed) => {
+            if parsed.status.error_code != 0 {
+                let error_msg = parsed.status.error_message.unwrap_or_default();
+                log_error("Listings API Error", &error_msg).unwrap_or(());
+                return Err(ApiError::from_status(parsed.status.error_code, error_msg));
+            }
+            Ok(parsed.data)
+        },
+        Err(_) if !http_status.is_success() => Err(ApiError::from_http(http_status)),
+        Err(e) => Err(ApiError::parse("Listings Parse Error", "listings response", e)),
+    }
+}
+
+/// Fetches the last `count` daily OHLCV candles for a coin (by CMC id), oldest first
+pub async fn fetch_ohlcv(api_key: &str, id: u64, count: u32, convert: &str) -> ApiResult<Vec<OhlcvQuote>> {
+    let client = &http_client();
+    let id = id.to_string();
+    let count = count.to_string();
+
+    let response = client
+        .get(CMC_OHLCV_URL)
+        .header("X-CMC_PRO_API_KEY", api_key)
+        .query(&[
+            ("id", id.as_str()),
+            ("count", count.as_str()),
+            ("time_period", "daily"),
+            ("convert", convert),
+        ])
+        .send()
+        .await?;
+
+    let http_status = response.status();
+    record_credits(response.headers());
+    let response_text = response.text().await?;
+
+    match serde_json::from_str::<OhlcvResponse>(&response_text) {
+        Ok(mut parsed) => {
+            if parsed.status.error_code != 0 {
+                let error_msg = parsed.status.error_message.unwrap_or_default();
+                log_error("OHLCV API Error", &error_msg).unwrap_or(());
+                return Err(ApiError::from_status(parsed.status.error_code, error_msg));
+            }
+            match parsed.data.remove(&id) {
+                Some(data) => Ok(data.quotes),
+                None => Err(ApiError::Parse(format!("No price history returned for id {}", id))),
+            }
+        },
+        Err(_) if !http_status.is_success() => Err(ApiError::from_http(http_status)),
+        Err(e) => Err(ApiError::parse("OHLCV Parse Error", "OHLCV response", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::logger::{self, LogLevel};
+
+    const QUOTES_OK: &str = r#"{
+        "status": {"timestamp": "2024-05-14T09:21:37.118Z", "error_code": 0, "error_message": null,
+                   "elapsed": 31, "credit_count": 1, "notice": null},
+        "data": {
+            "1": {"id": 1, "name": "Bitcoin", "symbol": "BTC", "slug": "bitcoin", "num_market_pairs": 11010,
+                  "quote": {"USD": {"price": 61523.42, "volume_24h": 27365139023.5, "volume_change_24h": -12.4,
+                                    "percent_change_1h": 0.21, "percent_change_24h": -1.73,
+                                    "percent_change_7d": -3.05, "percent_change_30d": -7.9,
+                                    "percent_change_60d": -2.2, "percent_change_90d": 18.4,
+                                    "market_cap": 1211836552103.4, "last_updated": "2024-05-14T09:20:00.000Z"}}},
+            "1027": {"id": 1027, "name": "Ethereum", "symbol": "ETH", "slug": "ethereum",
+                     "quote": {"USD": {"price": 2905.11, "volume_24h": null, "volume_change_24h": null,
+                                       "percent_change_1h": null, "percent_change_24h": 0.8,
+                                       "percent_change_7d": null, "percent_change_30d": null,
+                                       "percent_change_90d": null, "market_cap": null}}}
         }
+    }"#;
+
+    const INVALID_KEY: &str = r#"{
+        "status": {"timestamp": "2024-05-14T09:22:03.512Z", "error_code": 1002,
+                   "error_message": "API key missing.", "elapsed": 0, "credit_count": 0}
+    }"#;
+
+    const FEAR_GREED_OK: &str = r#"{
+        "data": [
+            {"timestamp": "1715644800", "value": 64, "value_classification": "Greed"},
+            {"timestamp": "1715558400", "value": 47, "value_classification": "Neutral"}
+        ],
+        "status": {"timestamp": "2024-05-14T09:23:11.004Z", "error_code": "0", "error_message": "SUCCESS",
+                   "elapsed": "4", "credit_count": 1}
+    }"#;
+
+    const FEAR_GREED_INVALID_KEY: &str = r#"{
+        "data": [],
+        "status": {"timestamp": "2024-05-14T09:23:40.771Z", "error_code": "1002",
+                   "error_message": "API key missing.", "elapsed": "0", "credit_count": 0}
+    }"#;
+
+    const DAILY_RANGES_OK: &str = r#"{
+        "status": {"timestamp": "2024-05-14T09:21:38.004Z", "error_code": 0, "error_message": null,
+                   "elapsed": 12, "credit_count": 1},
+        "data": {
+            "1": {"id": 1, "name": "Bitcoin", "symbol": "BTC", "last_updated": "2024-05-14T09:20:00.000Z",
+                  "time_open": "2024-05-14T00:00:00.000Z", "time_close": null,
+                  "quote": {"USD": {"open": 61700.0, "high": 62110.5, "low": 60890.25, "close": 61523.42,
+                                    "volume": 27365139023.5, "last_updated": "2024-05-14T09:20:00.000Z"}}}
+        }
+    }"#;
+
+    /// Cloudflare's plain-text reply when the request rate is exceeded
+    const RATE_LIMITED_HTML: &str = "<html><body><h1>429 Too Many Requests</h1></body></html>";
+
+    /// Keeps the parsers' error logging out of the working directory
+    fn quiet_logs() {
+        logger::init(std::env::temp_dir().join("cointracker-api-tests.log"), LogLevel::Error, u64::MAX);
+    }
+
+    #[test]
+    fn quotes_parse_success() {
+        quiet_logs();
+        let data = parse_quotes_response(StatusCode::OK, QUOTES_OK).expect("quotes should parse");
+        assert_eq!(data.len(), 2);
+        let btc = &data["1"];
+        assert_eq!(btc.symbol, "BTC");
+        assert_eq!(btc.slug.as_deref(), Some("bitcoin"));
+        assert_eq!(btc.quote["USD"].price, 61523.42);
+        assert_eq!(data["1027"].quote["USD"].volume_24h, None);
+    }
+
+    #[test]
+    fn quotes_invalid_key_is_auth_error() {
+        quiet_logs();
+        let result = parse_quotes_response(StatusCode::UNAUTHORIZED, INVALID_KEY);
+        assert!(matches!(result, Err(ApiError::Auth(message)) if message == "API key missing."));
+    }
+
+    #[test]
+    fn quotes_disabled_key_is_auth_error() {
+        quiet_logs();
+        let body = r#"{"status": {"error_code": 1007, "error_message": "This API Key has been disabled."}}"#;
+        let result = parse_quotes_response(StatusCode::FORBIDDEN, body);
+        assert!(matches!(result, Err(ApiError::Auth(_))));
+    }
+
+    #[test]
+    fn quotes_non_json_429_is_rate_limited() {
+        quiet_logs();
+        let result = parse_quotes_response(StatusCode::TOO_MANY_REQUESTS, RATE_LIMITED_HTML);
+        assert!(matches!(result, Err(ApiError::RateLimited(_))));
+    }
+
+    #[test]
+    fn quotes_malformed_body_is_parse_error() {
+        quiet_logs();
+        let result = parse_quotes_response(StatusCode::OK, r#"{"status": {"error_code": 0}, "data": {"1": {"id": "#);
+        assert!(matches!(result, Err(ApiError::Parse(_))));
+    }
+
+    #[test]
+    fn daily_ranges_fill_quotes() {
+        quiet_logs();
+        let mut data = parse_quotes_response(StatusCode::OK, QUOTES_OK).expect("quotes should parse");
+        let ranges = parse_daily_ranges_response(StatusCode::OK, DAILY_RANGES_OK).expect("ranges should parse");
+        apply_daily_ranges(&mut data, ranges, "USD");
+
+        let btc = &data["1"].quote["USD"];
+        assert_eq!(btc.high_24h, Some(62110.5));
+        assert_eq!(btc.low_24h, Some(60890.25));
+        // Coins without a candle keep an unknown range
+        assert_eq!(data["1027"].quote["USD"].high_24h, None);
+    }
+
+    #[test]
+    fn daily_ranges_plan_error_is_api_error() {
+        quiet_logs();
+        let body = r#"{"status": {"error_code": 1006, "error_message": "Your API Key subscription plan doesn't support this endpoint."}}"#;
+        let result = parse_daily_ranges_response(StatusCode::FORBIDDEN, body);
+        assert!(matches!(result, Err(ApiError::Api { code: 1006, .. })));
+    }
+
+    #[test]
+    fn fear_greed_parse_success() {
+        quiet_logs();
+        let data = parse_fear_greed_response(StatusCode::OK, FEAR_GREED_OK).expect("fear & greed should parse");
+        assert_eq!(data.len(), 2);
+        assert_eq!(data[0].value, 64);
+        assert_eq!(data[0].value_classification, "Greed");
+        assert!(data[0].date().is_some());
+    }
+
+    #[test]
+    fn fear_greed_invalid_key_is_auth_error() {
+        quiet_logs();
+        let result = parse_fear_greed_response(StatusCode::UNAUTHORIZED, FEAR_GREED_INVALID_KEY);
+        assert!(matches!(result, Err(ApiError::Auth(_))));
+    }
+
+    #[test]
+    fn fear_greed_non_json_429_is_rate_limited() {
+        quiet_logs();
+        let result = parse_fear_greed_response(StatusCode::TOO_MANY_REQUESTS, RATE_LIMITED_HTML);
+        assert!(matches!(result, Err(ApiError::RateLimited(_))));
+    }
+
+    #[test]
+    fn fear_greed_malformed_body_is_parse_error() {
+        quiet_logs();
+        let result = parse_fear_greed_response(StatusCode::OK, r#"{"data": [{"timestamp": "1715644800", "value": "high"}]}"#);
+        assert!(matches!(result, Err(ApiError::Parse(_))));
     }
 }
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use anyhow::Result;
+use chrono::{DateTime, Local, TimeZone};
+use serde::{Deserialize, Serialize};
+
+use crate::models::crypto::CryptoData;
+
+const DEFAULT_CACHE_DIR: &str = ".cache";
+const PRICE_CACHE_FILE: &str = "prices.json";
+const VALUE_HISTORY_FILE: &str = "value_history.json";
+const LAST_VIEWED_FILE: &str = "last_viewed.json";
+/// Oldest snapshots are dropped beyond this many to bound file growth
+const MAX_VALUE_HISTORY_POINTS: usize = 500;
+
+/// Cache directory set with `set_dir`, otherwise `DEFAULT_CACHE_DIR`
+static CACHE_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Total portfolio value at one point in time
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ValuePoint {
+    /// Unix timestamp (seconds)
+    pub timestamp: i64,
+    pub value: f64,
+}
+
+/// Prices as they were when the app was last closed
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LastViewed {
+    /// Unix timestamp (seconds) of when the app was closed
+    pub viewed_at: i64,
+    /// Currency the prices are quoted in
+    pub currency: String,
+    /// Price by symbol
+    pub prices: HashMap<String, f64>,
+}
+
+impl LastViewed {
+    pub fn local_time(&self) -> Option<DateTime<Local>> {
+        Local.timestamp_opt(self.viewed_at, 0).single()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PriceCache {
+    /// Unix timestamp (seconds) of the fetch that produced `data`
+    fetched_at: i64,
+    data: HashMap<String, CryptoData>,
+}
+
+/// Stores the cache under `dir` instead of `.cache` in the working directory. Only the
+/// first call has any effect
+pub fn set_dir(dir: PathBuf) {
+    let _ = CACHE_DIR.set(dir);
+}
+
+fn cache_path(file: &str) -> PathBuf {
+    CACHE_DIR.get()
+        .map(PathBuf::as_path)
+        .unwrap_or(Path::new(DEFAULT_CACHE_DIR))
+        .join(file)
+}
+
+pub fn price_cache_path() -> PathBuf {
+    cache_path(PRICE_CACHE_FILE)
+}
+
+/// Writes the latest price data to the on-disk cache
+pub fn save_prices(data: &HashMap<String, CryptoData>, fetched_at: DateTime<Local>) -> Result<()> {
+    let path = price_cache_path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let cache = PriceCache {
+        fetched_at: fetched_at.timestamp(),
+        data: data.clone(),
+    };
+    fs::write(path, serde_json::to_string(&cache)?)?;
+    Ok(())
+}
+
+/// Loads the cached price data and the time it was fetched, if a readable cache exists
+pub fn load_prices() -> Option<(HashMap<String, CryptoData>, DateTime<Local>)> {
+    let contents = fs::read_to_string(price_cache_path()).ok()?;
+    let cache: PriceCache = serde_json::from_str(&contents).ok()?;
+    let fetched_at = Local.timestamp_opt(cache.fetched_at, 0).single()?;
+    Some((cache.data, fetched_at))
+}
+
+/// Loads the stored portfolio value history, oldest first. Missing or unreadable
+/// history is treated as empty
+pub fn load_value_history() -> Vec<ValuePoint> {
+    fs::read_to_string(cache_path(VALUE_HISTORY_FILE)).ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Appends a snapshot to the stored history, trimming it to the newest
+/// `MAX_VALUE_HISTORY_POINTS`, and returns the updated series
+pub fn append_value_snapshot(point: ValuePoint) -> Result<Vec<ValuePoint>> {
+    let mut history = load_value_history();
+    history.push(point);
+    if history.len() > MAX_VALUE_HISTORY_POINTS {
+        history.drain(..history.len() - MAX_VALUE_HISTORY_POINTS);
+    }
+    save_value_history(&history)?;
+    Ok(history)
+}
+
+/// Replaces the stored value history
+pub fn save_value_history(history: &[ValuePoint]) -> Result<()> {
+    let path = cache_path(VALUE_HISTORY_FILE);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_string(history)?)?;
+    Ok(())
+}
+
+/// Writes the prices seen at the end of a session, replacing the previous snapshot
+pub fn save_last_viewed(snapshot: &LastViewed) -> Result<()> {
+    let path = cache_path(LAST_VIEWED_FILE);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_string(snapshot)?)?;
+    Ok(())
+}
+
+/// Loads the prices from the end of the previous session, if a readable snapshot exists
+pub fn load_last_viewed() -> Option<LastViewed> {
+    let contents = fs::read_to_string(cache_path(LAST_VIEWED_FILE)).ok()?;
+    serde_json::from_str(&contents).ok()
+}
@@ -0,0 +1,20 @@
+use std::sync::Mutex;
+
+use anyhow::Result;
+use arboard::Clipboard;
+
+/// Kept open for the life of the app: on X11 the copied text is only served while
+/// the clipboard handle that set it is alive
+static CLIPBOARD: Mutex<Option<Clipboard>> = Mutex::new(None);
+
+/// Puts `text` on the system clipboard. Fails on headless systems without one
+pub fn copy(text: &str) -> Result<()> {
+    let mut clipboard = CLIPBOARD.lock().unwrap_or_else(|e| e.into_inner());
+    if clipboard.is_none() {
+        *clipboard = Some(Clipboard::new()?);
+    }
+    if let Some(clipboard) = clipboard.as_mut() {
+        clipboard.set_text(text)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,321 @@
+use futures::future::join_all;
+use itertools::Itertools;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::models::crypto::{CryptoData, OhlcvQuote, OhlcvValues, Quote};
+use crate::models::fear_greed::FearGreedData;
+use crate::models::market::{GlobalMetrics, GlobalQuote};
+use crate::services::api::{http_client, missing_tokens, send_with_retry, ApiError, ApiResult, PriceFetch};
+use crate::services::logger::{log_error, log_info};
+
+const COINGECKO_MARKETS_URL: &str = "https://api.coingecko.com/api/v3/coins/markets";
+const COINGECKO_GLOBAL_URL: &str = "https://api.coingecko.com/api/v3/global";
+const COINGECKO_COINS_URL: &str = "https://api.coingecko.com/api/v3/coins";
+/// CoinGecko doesn't publish a fear & greed index; alternative.me's is free and keyless
+const FEAR_GREED_URL: &str = "https://api.alternative.me/fng/";
+/// Largest page `/coins/markets` returns, which caps the ids per request
+const MAX_IDS_PER_REQUEST: usize = 250;
+
+#[derive(Debug, Deserialize)]
+struct MarketCoin {
+    id: String,
+    symbol: String,
+    name: String,
+    current_price: Option<f64>,
+    market_cap: Option<f64>,
+    total_volume: Option<f64>,
+    price_change_percentage_1h_in_currency: Option<f64>,
+    price_change_percentage_24h_in_currency: Option<f64>,
+    price_change_percentage_7d_in_currency: Option<f64>,
+    price_change_percentage_30d_in_currency: Option<f64>,
+    high_24h: Option<f64>,
+    low_24h: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GlobalResponse {
+    data: GlobalData,
+}
+
+#[derive(Debug, Deserialize)]
+struct GlobalData {
+    active_cryptocurrencies: u64,
+    total_market_cap: HashMap<String, f64>,
+    total_volume: HashMap<String, f64>,
+    market_cap_percentage: HashMap<String, f64>,
+    market_cap_change_percentage_24h_usd: Option<f64>,
+}
+
+/// `/coins/{id}/market_chart`: [unix millis, price] pairs, oldest first
+#[derive(Debug, Deserialize)]
+struct MarketChartResponse {
+    prices: Vec<(f64, f64)>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AlternativeFearGreedResponse {
+    data: Vec<AlternativeFearGreedData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AlternativeFearGreedData {
+    value: String,
+    value_classification: String,
+    timestamp: String,
+}
+
+/// Fetches market data for CoinGecko coin ids (which match CMC slugs for most coins),
+/// mapped onto the CMC structures. Coins without a price in `convert` get no quote
+pub async fn fetch_prices(
+    api_key: &str,
+    token_names: &[String],
+    convert: &str,
+    batch_size: usize,
+    max_retries: u32,
+    retry_base_ms: u64,
+) -> ApiResult<PriceFetch> {
+    let client = &http_client();
+
+    let batches = token_names.chunks(batch_size.clamp(1, MAX_IDS_PER_REQUEST)).collect::<Vec<_>>();
+    let ids = batches.iter()
+        .map(|batch| batch.iter().map(|token| token.to_lowercase()).join(","))
+        .collect::<Vec<_>>();
+    let results = join_all(ids.iter().map(|ids| {
+        fetch_market_batch(client, api_key, ids, convert, max_retries, retry_base_ms)
+    })).await;
+
+    // Unknown ids are left out of the response rather than reported
+    let mut data = HashMap::new();
+    let mut errors = Vec::new();
+    let mut missing = Vec::new();
+    for (batch, result) in batches.iter().zip(results) {
+        match result {
+            Ok(found) => {
+                missing.extend(missing_tokens(batch, &found));
+                data.extend(found);
+            }
+            Err(e) => errors.push(e),
+        }
+    }
+
+    if data.is_empty() && !errors.is_empty() {
+        return Err(errors.remove(0));
+    }
+
+    if !errors.is_empty() {
+        log_error("Price Fetch", &format!("{} of {} batches failed", errors.len(), batches.len())).unwrap_or(());
+    }
+
+    Ok(PriceFetch {
+        data,
+        warnings: errors.iter().map(|e| e.to_string()).collect(),
+        missing,
+    })
+}
+
+async fn fetch_market_batch(
+    client: &reqwest::Client,
+    api_key: &str,
+    ids: &str,
+    convert: &str,
+    max_retries: u32,
+    retry_base_ms: u64,
+) -> ApiResult<HashMap<String, CryptoData>> {
+    let per_page = MAX_IDS_PER_REQUEST.to_string();
+    let coins = fetch_markets(
+        client, api_key, &[("ids", ids), ("per_page", per_page.as_str())], convert, max_retries, retry_base_ms,
+    ).await?;
+    Ok(coins.into_iter()
+        .map(|coin| (coin.id.clone(), to_crypto_data(coin, convert)))
+        .collect())
+}
+
+/// `/coins/markets` with `params` on top of the currency and change windows every
+/// caller needs
+async fn fetch_markets(
+    client: &reqwest::Client,
+    api_key: &str,
+    params: &[(&str, &str)],
+    convert: &str,
+    max_retries: u32,
+    retry_base_ms: u64,
+) -> ApiResult<Vec<MarketCoin>> {
+    let vs_currency = convert.to_lowercase();
+    let response = send_with_retry(
+        || with_key(
+            client
+                .get(COINGECKO_MARKETS_URL)
+                .query(&[
+                    ("vs_currency", vs_currency.as_str()),
+                    ("price_change_percentage", "1h,24h,7d,30d"),
+                ])
+                .query(params),
+            api_key,
+        ),
+        max_retries,
+        retry_base_ms,
+    ).await?;
+
+    let response_text = success_text(response).await?;
+    serde_json::from_str(&response_text)
+        .map_err(|e| ApiError::parse("CoinGecko Parse Error", "CoinGecko response", e))
+}
+
+/// Adds the demo API key, if any. Keyless access works with tighter rate limits
+fn with_key(request: reqwest::RequestBuilder, api_key: &str) -> reqwest::RequestBuilder {
+    if api_key.is_empty() {
+        request
+    } else {
+        request.header("x-cg-demo-api-key", api_key)
+    }
+}
+
+/// The body of a successful response; any other status is logged and returned as an error
+async fn success_text(response: reqwest::Response) -> ApiResult<String> {
+    let status = response.status();
+    let response_text = response.text().await?;
+    if !status.is_success() {
+        log_error("CoinGecko API Error", &format!("HTTP {}: {}", status, response_text)).unwrap_or(());
+        return Err(ApiError::from_http(status));
+    }
+    Ok(response_text)
+}
+
+/// The top `limit` coins by market cap, like CMC's listings endpoint
+pub async fn fetch_listings(
+    api_key: &str,
+    limit: u32,
+    convert: &str,
+    max_retries: u32,
+    retry_base_ms: u64,
+) -> ApiResult<Vec<CryptoData>> {
+    let per_page = limit.clamp(1, MAX_IDS_PER_REQUEST as u32).to_string();
+    let coins = fetch_markets(
+        &http_client(),
+        api_key,
+        &[("order", "market_cap_desc"), ("per_page", per_page.as_str()), ("page", "1")],
+        convert,
+        max_retries,
+        retry_base_ms,
+    ).await?;
+    Ok(coins.into_iter().map(|coin| to_crypto_data(coin, convert)).collect())
+}
+
+/// Total market cap, volume and dominance from `/global`, in the CMC shape. CoinGecko
+/// only reports the market cap change in USD and no volume change
+pub async fn fetch_global_metrics(api_key: &str, convert: &str) -> ApiResult<GlobalMetrics> {
+    let response = with_key(http_client().get(COINGECKO_GLOBAL_URL), api_key).send().await?;
+    let response_text = success_text(response).await?;
+    let data = serde_json::from_str::<GlobalResponse>(&response_text)
+        .map_err(|e| ApiError::parse("CoinGecko Parse Error", "global metrics response", e))?
+        .data;
+
+    let vs_currency = convert.to_lowercase();
+    let quote = match (data.total_market_cap.get(&vs_currency), data.total_volume.get(&vs_currency)) {
+        (Some(market_cap), Some(volume)) => HashMap::from([(convert.to_string(), GlobalQuote {
+            total_market_cap: *market_cap,
+            total_volume_24h: *volume,
+            total_market_cap_yesterday_percentage_change: data.market_cap_change_percentage_24h_usd
+                .filter(|_| vs_currency == "usd"),
+            total_volume_24h_yesterday_percentage_change: None,
+        })]),
+        _ => HashMap::new(),
+    };
+    Ok(GlobalMetrics {
+        active_cryptocurrencies: data.active_cryptocurrencies,
+        btc_dominance: data.market_cap_percentage.get("btc").copied().unwrap_or(0.0),
+        eth_dominance: data.market_cap_percentage.get("eth").copied(),
+        quote,
+    })
+}
+
+/// Daily closing prices for a CoinGecko coin id over the last `count` days, oldest first,
+/// as CMC-style candles
+pub async fn fetch_ohlcv(api_key: &str, coin_id: &str, count: u32, convert: &str) -> ApiResult<Vec<OhlcvQuote>> {
+    let url = format!("{}/{}/market_chart", COINGECKO_COINS_URL, coin_id);
+    let days = count.to_string();
+    let vs_currency = convert.to_lowercase();
+    let response = with_key(
+        http_client()
+            .get(url)
+            .query(&[("vs_currency", vs_currency.as_str()), ("days", days.as_str()), ("interval", "daily")]),
+        api_key,
+    ).send().await?;
+    let response_text = success_text(response).await?;
+    let chart = serde_json::from_str::<MarketChartResponse>(&response_text)
+        .map_err(|e| ApiError::parse("CoinGecko Parse Error", "market chart response", e))?;
+
+    // CoinGecko returns `days + 1` points, the last being the latest price
+    let skip = chart.prices.len().saturating_sub(count as usize);
+    Ok(chart.prices.into_iter()
+        .skip(skip)
+        .filter_map(|(millis, close)| {
+            let time = chrono::DateTime::from_timestamp_millis(millis as i64)?;
+            Some(OhlcvQuote {
+                time_open: time.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string(),
+                quote: HashMap::from([(convert.to_string(), OhlcvValues { close })]),
+            })
+        })
+        .collect())
+}
+
+fn to_crypto_data(coin: MarketCoin, convert: &str) -> CryptoData {
+    let mut quote = HashMap::new();
+    if let Some(price) = coin.current_price {
+        quote.insert(convert.to_string(), Quote {
+            price,
+            volume_24h: coin.total_volume,
+            volume_change_24h: None,
+            percent_change_1h: coin.price_change_percentage_1h_in_currency,
+            percent_change_24h: coin.price_change_percentage_24h_in_currency,
+            percent_change_7d: coin.price_change_percentage_7d_in_currency,
+            percent_change_30d: coin.price_change_percentage_30d_in_currency,
+            percent_change_90d: None,
+            market_cap: coin.market_cap,
+            high_24h: coin.high_24h,
+            low_24h: coin.low_24h,
+        });
+    }
+
+    CryptoData {
+        // CoinGecko has no numeric ids; 0 marks a coin without a CMC id. The CoinGecko id
+        // is kept as the slug
+        id: 0,
+        name: coin.name,
+        slug: Some(coin.id),
+        symbol: coin.symbol.to_uppercase(),
+        quote,
+    }
+}
+
+/// Fetches historical fear and greed index data from alternative.me, newest first
+pub async fn fetch_fear_greed(limit: &str) -> ApiResult<Vec<FearGreedData>> {
+    let client = &http_client();
+
+    let response = client
+        .get(FEAR_GREED_URL)
+        .query(&[("limit", limit)])
+        .send()
+        .await?;
+
+    let http_status = response.status();
+    let response_text = response.text().await?;
+
+    match serde_json::from_str::<AlternativeFearGreedResponse>(&response_text) {
+        Ok(parsed) => {
+            let data = parsed.data.into_iter()
+                .filter_map(|point| Some(FearGreedData {
+                    value: point.value.parse().ok()?,
+                    value_classification: point.value_classification,
+                    timestamp: point.timestamp,
+                }))
+                .collect::<Vec<_>>();
+            log_info("Fear & Greed",
+                &format!("Successfully fetched {} data points", data.len())).unwrap_or(());
+            Ok(data)
+        }
+        Err(_) if !http_status.is_success() => Err(ApiError::from_http(http_status)),
+        Err(e) => Err(ApiError::parse("Fear & Greed Parse Error", "Fear & Greed response", e)),
+    }
+}
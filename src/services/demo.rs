@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{Duration, Utc};
+use serde_json::json;
+
+use crate::models::config::Config;
+use crate::models::crypto::{CryptoData, OhlcvQuote, OhlcvValues, Quote};
+use crate::models::fear_greed::FearGreedData;
+use crate::models::market::{GlobalMetrics, GlobalQuote};
+use crate::services::cache::{self, ValuePoint};
+
+/// Provider name that selects the synthetic data source
+pub const PROVIDER_NAME: &str = "demo";
+/// Prefix of the scratch directory, under the system temp dir, holding the demo config,
+/// cache and log. The process id is appended so concurrent demo runs stay apart
+const DEMO_DIR_PREFIX: &str = "cointracker-demo";
+/// Hourly snapshots seeded into the demo value history
+const VALUE_HISTORY_POINTS: i64 = 72;
+
+/// A coin in the synthetic market: (CMC id, slug, name, symbol, price, 1h, 24h, 7d, 30d
+/// change in percent, market cap)
+type DemoCoin = (u64, &'static str, &'static str, &'static str, f64, f64, f64, f64, f64, f64);
+
+/// Chosen so the demo portfolio has both winners and losers, and the 24h column shows
+/// every change band
+const DEMO_COINS: [DemoCoin; 8] = [
+    (1, "bitcoin", "Bitcoin", "BTC", 64_250.0, 0.35, 2.41, 6.8, 12.3, 1_265_000_000_000.0),
+    (1027, "ethereum", "Ethereum", "ETH", 3_120.0, -0.22, -1.35, 4.1, -3.9, 375_000_000_000.0),
+    (825, "tether", "Tether USDt", "USDT", 1.0, 0.0, 0.01, -0.02, 0.01, 112_000_000_000.0),
+    (5426, "solana", "Solana", "SOL", 148.6, 1.12, 7.85, 18.4, 31.0, 68_500_000_000.0),
+    (74, "dogecoin", "Dogecoin", "DOGE", 0.1243, -0.64, -6.2, -11.5, 4.2, 18_100_000_000.0),
+    (2010, "cardano", "Cardano", "ADA", 0.4518, 0.08, -0.4, -2.7, -14.8, 16_000_000_000.0),
+    (5805, "avalanche", "Avalanche", "AVAX", 27.35, -0.91, -3.6, 9.2, -8.1, 11_100_000_000.0),
+    (1975, "chainlink", "Chainlink", "LINK", 14.92, 0.47, 1.05, -4.4, 6.6, 9_000_000_000.0),
+];
+
+fn to_crypto_data(coin: &DemoCoin, convert: &str) -> CryptoData {
+    let (id, slug, name, symbol, price, change_1h, change_24h, change_7d, change_30d, market_cap) = *coin;
+    let quote = Quote {
+        price,
+        volume_24h: Some(market_cap * 0.035),
+        volume_change_24h: Some(change_24h * 2.5),
+        percent_change_1h: Some(change_1h),
+        percent_change_24h: Some(change_24h),
+        percent_change_7d: Some(change_7d),
+        percent_change_30d: Some(change_30d),
+        percent_change_90d: Some(change_30d * 1.8),
+        market_cap: Some(market_cap),
+        high_24h: Some(price * (1.0 + change_24h.abs().max(0.5) / 100.0)),
+        low_24h: Some(price * (1.0 - change_24h.abs().max(0.5) / 100.0)),
+    };
+    CryptoData {
+        id,
+        name: name.to_string(),
+        slug: Some(slug.to_string()),
+        symbol: symbol.to_string(),
+        quote: HashMap::from([(convert.to_string(), quote)]),
+    }
+}
+
+fn find_coin(token: &str) -> Option<&'static DemoCoin> {
+    DEMO_COINS.iter().find(|(_, slug, name, symbol, ..)| {
+        token.eq_ignore_ascii_case(slug) || token.eq_ignore_ascii_case(name) || token.eq_ignore_ascii_case(symbol)
+    })
+}
+
+/// Quotes for the known demo coins among `token_names`, keyed like CMC's response.
+/// Other tokens get no data, as an unknown slug would from the real API
+pub fn prices(token_names: &[String], convert: &str) -> HashMap<String, CryptoData> {
+    token_names.iter()
+        .filter_map(|token| find_coin(token))
+        .map(|coin| (coin.0.to_string(), to_crypto_data(coin, convert)))
+        .collect()
+}
+
+/// The demo coins ranked by market cap, like the listings endpoint
+pub fn listings(limit: u32, convert: &str) -> Vec<CryptoData> {
+    let mut coins: Vec<&DemoCoin> = DEMO_COINS.iter().collect();
+    coins.sort_by(|a, b| b.9.total_cmp(&a.9));
+    coins.into_iter()
+        .take(limit as usize)
+        .map(|coin| to_crypto_data(coin, convert))
+        .collect()
+}
+
+pub fn global_metrics(convert: &str) -> GlobalMetrics {
+    GlobalMetrics {
+        active_cryptocurrencies: 9_874,
+        btc_dominance: 54.2,
+        eth_dominance: Some(16.1),
+        quote: HashMap::from([(convert.to_string(), GlobalQuote {
+            total_market_cap: 2_330_000_000_000.0,
+            total_volume_24h: 86_400_000_000.0,
+            total_market_cap_yesterday_percentage_change: Some(1.74),
+            total_volume_24h_yesterday_percentage_change: Some(-8.3),
+        })]),
+    }
+}
+
+/// Daily fear & greed readings swinging between fear and greed, newest first
+pub fn fear_greed(limit: &str) -> Vec<FearGreedData> {
+    let days = limit.parse::<i64>().unwrap_or(30);
+    let today = Utc::now().date_naive().and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+    (0..days)
+        .map(|day| {
+            let t = day as f64;
+            let value = (52.0 + 30.0 * (t / 4.5).sin() + 8.0 * (t / 2.3).cos()).round().clamp(1.0, 99.0) as u64;
+            FearGreedData {
+                timestamp: (today - Duration::days(day)).timestamp().to_string(),
+                value,
+                value_classification: classify(value).to_string(),
+            }
+        })
+        .collect()
+}
+
+fn classify(value: u64) -> &'static str {
+    match value {
+        0..=24 => "Extreme Fear",
+        25..=44 => "Fear",
+        45..=55 => "Neutral",
+        56..=75 => "Greed",
+        _ => "Extreme Greed",
+    }
+}
+
+/// `count` daily candles, oldest first, drifting in from the coin's 30d change and
+/// ending at its current price. Unknown ids get no history
+pub fn ohlcv(id: u64, count: u32, convert: &str) -> Vec<OhlcvQuote> {
+    let coin = match DEMO_COINS.iter().find(|coin| coin.0 == id) {
+        Some(coin) => coin,
+        None => return Vec::new(),
+    };
+    let (price, change_30d) = (coin.4, coin.8);
+    let today = Utc::now().date_naive();
+    let count = i64::from(count);
+    (0..count)
+        .map(|i| {
+            let days_ago = count - 1 - i;
+            let drift = 1.0 - change_30d / 100.0 * days_ago as f64 / 30.0;
+            let wave = 1.0 + 0.03 * ((i as f64 + id as f64) / 2.7).sin();
+            let close = if days_ago == 0 { price } else { price * drift * wave };
+            OhlcvQuote {
+                time_open: format!("{}T00:00:00.000Z", today - Duration::days(days_ago)),
+                quote: HashMap::from([(convert.to_string(), OhlcvValues { close })]),
+            }
+        })
+        .collect()
+}
+
+/// A portfolio with gains and losses, staking, target allocations, notes, groups and an
+/// alert, so every view has something to show
+fn demo_config(dir: &Path) -> Result<Config> {
+    let config = json!({
+        "provider": PROVIDER_NAME,
+        "api_key": "",
+        "fear_and_greed_limit": "30",
+        "show_rsi": true,
+        "log_path": dir.join("crypto_tracker.log").to_string_lossy(),
+        "tokens": [
+            {"name": "bitcoin", "in_watchlist": true, "in_portfolio": true, "pinned": true, "owned": 0.42,
+             "avg_buy_price": 41_800.0, "target_allocation": 45.0, "tags": ["long-term"], "group": "L1",
+             "note": "Cold storage"},
+            {"name": "ethereum", "in_watchlist": true, "in_portfolio": true, "owned": 4.5,
+             "avg_buy_price": 3_480.0, "target_allocation": 30.0, "staking_apr": 3.2,
+             "tags": ["staking"], "group": "L1"},
+            {"name": "solana", "in_watchlist": true, "in_portfolio": true, "owned": 60.0,
+             "avg_buy_price": 96.5, "target_allocation": 15.0, "staking_apr": 6.8,
+             "tags": ["staking"], "group": "L1"},
+            {"name": "dogecoin", "in_watchlist": true, "in_portfolio": true, "owned": 12_000.0,
+             "avg_buy_price": 0.165, "group": "Memes"},
+            {"name": "cardano", "in_watchlist": true, "in_portfolio": true, "owned": 1_500.0,
+             "avg_buy_price": 0.62, "target_allocation": 5.0, "group": "L1"},
+            {"name": "tether", "in_watchlist": true, "in_portfolio": true, "owned": 2_500.0,
+             "avg_buy_price": 1.0, "group": "Stablecoins"},
+            {"name": "chainlink", "in_watchlist": true, "in_portfolio": false, "group": "DeFi"},
+            {"name": "avalanche", "in_watchlist": true, "in_portfolio": false, "group": "L1"},
+        ],
+        "alerts": [
+            {"token": "bitcoin", "direction": "above", "price": 70_000.0},
+        ],
+    });
+    Ok(serde_json::from_value(config)?)
+}
+
+/// Hourly portfolio values over the last few days, ending near today's total
+fn demo_value_history(config: &Config) -> Vec<ValuePoint> {
+    let names: Vec<String> = config.tokens.iter().map(|token| token.name.clone()).collect();
+    let data = prices(&names, &config.convert_currency);
+    let total: f64 = config.tokens.iter()
+        .filter_map(|token| {
+            let crypto = data.values().find(|crypto| token.matches_crypto(crypto))?;
+            Some(token.owned? * crypto.quote.get(&config.convert_currency)?.price)
+        })
+        .sum();
+
+    let now = Utc::now().timestamp();
+    (0..VALUE_HISTORY_POINTS)
+        .map(|i| {
+            let hours_ago = VALUE_HISTORY_POINTS - 1 - i;
+            let t = i as f64;
+            let drift = 1.0 - 0.0009 * hours_ago as f64;
+            let wave = 1.0 + 0.012 * (t / 5.0).sin() + 0.006 * (t / 1.7).cos();
+            ValuePoint { timestamp: now - hours_ago * 3600, value: total * drift * wave }
+        })
+        .collect()
+}
+
+/// A demo run's scratch directory, deleted when dropped as the app exits
+pub struct DemoDir {
+    path: PathBuf,
+}
+
+impl DemoDir {
+    pub fn config_path(&self) -> PathBuf {
+        self.path.join("config.json")
+    }
+}
+
+impl Drop for DemoDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// Writes a fresh demo config and value history to a scratch directory of this process
+/// and points the cache there, so demo runs never read or overwrite the real config,
+/// cache or API key, nor each other's files
+pub fn setup() -> Result<DemoDir> {
+    let dir = DemoDir {
+        path: std::env::temp_dir().join(format!("{}-{}", DEMO_DIR_PREFIX, std::process::id())),
+    };
+    // Left behind by a crashed run that had the same process id
+    let _ = fs::remove_dir_all(&dir.path);
+    fs::create_dir_all(&dir.path)
+        .with_context(|| format!("Failed to create demo directory {}", dir.path.display()))?;
+    cache::set_dir(dir.path.join("cache"));
+
+    let config = demo_config(&dir.path)?;
+    config.save(&dir.config_path()).context("Failed to write demo config")?;
+    cache::save_value_history(&demo_value_history(&config)).context("Failed to write demo value history")?;
+    Ok(dir)
+}
@@ -1,31 +1,114 @@
 use std::fs::OpenOptions;
 use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 use chrono::Local;
 use anyhow::Result;
 
+const DEFAULT_LOG_PATH: &str = "crypto_tracker.log";
+const DEFAULT_MAX_LOG_BYTES: u64 = 10 * 1024 * 1024;
+/// Rotated logs kept next to the active one (`.1` is the newest)
+const LOG_BACKUPS: u32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Error,
+    Info,
+    Debug,
+}
+
+impl LogLevel {
+    /// Parses "error", "info" or "debug" (case-insensitive); anything else means info
+    pub fn from_name(name: &str) -> Self {
+        match name.to_lowercase().as_str() {
+            "error" => LogLevel::Error,
+            "debug" => LogLevel::Debug,
+            _ => LogLevel::Info,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            LogLevel::Error => "ERROR",
+            LogLevel::Info => "INFO",
+            LogLevel::Debug => "DEBUG",
+        }
+    }
+}
+
+struct LoggerSettings {
+    path: PathBuf,
+    level: LogLevel,
+    max_bytes: u64,
+}
+
+static SETTINGS: OnceLock<LoggerSettings> = OnceLock::new();
+
+/// Sets the log file, the most verbose level that gets written and the size at which
+/// the file is rotated. Until this is called, messages up to info go to
+/// `crypto_tracker.log` in the working directory
+pub fn init(path: PathBuf, level: LogLevel, max_bytes: u64) {
+    let _ = SETTINGS.set(LoggerSettings { path, level, max_bytes });
+}
+
 pub fn log_error(category: &str, message: &str) -> Result<()> {
-    log_message("ERROR", category, message)
+    log_message(LogLevel::Error, category, message)
 }
 
 pub fn log_info(category: &str, message: &str) -> Result<()> {
-    log_message("INFO", category, message)
+    log_message(LogLevel::Info, category, message)
+}
+
+pub fn log_debug(category: &str, message: &str) -> Result<()> {
+    log_message(LogLevel::Debug, category, message)
 }
 
-fn log_message(level: &str, category: &str, message: &str) -> Result<()> {
+fn log_message(level: LogLevel, category: &str, message: &str) -> Result<()> {
+    let (path, threshold, max_bytes) = match SETTINGS.get() {
+        Some(settings) => (settings.path.clone(), settings.level, settings.max_bytes),
+        None => (PathBuf::from(DEFAULT_LOG_PATH), LogLevel::Info, DEFAULT_MAX_LOG_BYTES),
+    };
+    if level > threshold {
+        return Ok(());
+    }
+
+    if std::fs::metadata(&path).is_ok_and(|meta| meta.len() >= max_bytes) {
+        rotate(&path)?;
+    }
+
     let now = Local::now();
     let mut file = OpenOptions::new()
         .create(true)
         .append(true)
-        .open("crypto_tracker.log")?;
+        .open(path)?;
 
     writeln!(
         file,
         "[{}] {} - {}: {}", 
         now.format("%Y-%m-%d %H:%M:%S"),
-        level,
+        level.label(),
         category,
         message
     )?;
 
     Ok(())
 }
+
+/// Shifts `log.1`..`log.N-1` up by one (dropping the oldest) and moves the active
+/// log to `log.1`, so the next write starts a fresh file
+fn rotate(path: &Path) -> Result<()> {
+    let backup = |n: u32| {
+        let mut name = path.as_os_str().to_owned();
+        name.push(format!(".{}", n));
+        PathBuf::from(name)
+    };
+
+    for n in (1..LOG_BACKUPS).rev() {
+        let from = backup(n);
+        if from.exists() {
+            std::fs::rename(&from, backup(n + 1))?;
+        }
+    }
+    std::fs::rename(path, backup(1))?;
+    Ok(())
+}
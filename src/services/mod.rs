@@ -1,2 +1,9 @@
 pub mod api;
+pub mod cache;
+pub mod clipboard;
+pub mod coingecko;
+pub mod demo;
 pub mod logger;
+pub mod notifier;
+pub mod provider;
+pub mod server;
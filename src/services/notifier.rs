@@ -0,0 +1,12 @@
+use anyhow::Result;
+use notify_rust::Notification;
+
+/// Shows a desktop notification
+pub fn notify(summary: &str, body: &str) -> Result<()> {
+    Notification::new()
+        .appname("cointracker")
+        .summary(summary)
+        .body(body)
+        .show()?;
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::models::config::Config;
+use crate::models::crypto::{CryptoData, OhlcvQuote};
+use crate::models::fear_greed::FearGreedData;
+use crate::models::market::GlobalMetrics;
+use crate::services::api::PriceFetch;
+use crate::services::{api, coingecko, demo};
+
+/// A source of prices and fear & greed data, selected with the `provider` config option
+#[async_trait]
+pub trait PriceProvider: Send + Sync {
+    async fn fetch_prices(&self, token_names: &[String], convert: &str) -> Result<PriceFetch>;
+    async fn fetch_fear_greed(&self, limit: &str) -> Result<Vec<FearGreedData>>;
+    async fn fetch_global_metrics(&self, convert: &str) -> Result<GlobalMetrics>;
+    /// Daily candles for a coin, oldest first
+    async fn fetch_ohlcv(&self, coin: &CryptoData, count: u32, convert: &str) -> Result<Vec<OhlcvQuote>>;
+    async fn fetch_listings(&self, limit: u32, convert: &str) -> Result<Vec<CryptoData>>;
+}
+
+/// Request settings shared by every provider
+struct RequestSettings {
+    api_key: String,
+    batch_size: usize,
+    max_retries: u32,
+    retry_base_ms: u64,
+}
+
+pub struct CoinMarketCap(RequestSettings);
+
+pub struct CoinGecko(RequestSettings);
+
+/// Deterministic synthetic data for `--demo`, without any network access
+pub struct Demo;
+
+/// Builds the provider named in the config. Unknown names fall back to CoinMarketCap
+pub fn from_config(config: &Config) -> Box<dyn PriceProvider> {
+    let settings = RequestSettings {
+        api_key: config.api_key.clone(),
+        batch_size: config.batch_size,
+        max_retries: config.max_retries,
+        retry_base_ms: config.retry_base_ms,
+    };
+    match config.provider.to_lowercase().as_str() {
+        "coingecko" => Box::new(CoinGecko(settings)),
+        demo::PROVIDER_NAME => Box::new(Demo),
+        _ => Box::new(CoinMarketCap(settings)),
+    }
+}
+
+#[async_trait]
+impl PriceProvider for CoinMarketCap {
+    async fn fetch_prices(&self, token_names: &[String], convert: &str) -> Result<PriceFetch> {
+        let s = &self.0;
+        Ok(api::fetch_prices(&s.api_key, token_names, convert, s.batch_size, s.max_retries, s.retry_base_ms).await?)
+    }
+
+    async fn fetch_fear_greed(&self, limit: &str) -> Result<Vec<FearGreedData>> {
+        Ok(api::fetch_fear_greed(&self.0.api_key, limit).await?)
+    }
+
+    async fn fetch_global_metrics(&self, convert: &str) -> Result<GlobalMetrics> {
+        Ok(api::fetch_global_metrics(&self.0.api_key, convert).await?)
+    }
+
+    async fn fetch_ohlcv(&self, coin: &CryptoData, count: u32, convert: &str) -> Result<Vec<OhlcvQuote>> {
+        // Coins cached from another provider have no CMC id
+        if coin.id == 0 {
+            anyhow::bail!("No CoinMarketCap id for {}", coin.symbol);
+        }
+        Ok(api::fetch_ohlcv(&self.0.api_key, coin.id, count, convert).await?)
+    }
+
+    async fn fetch_listings(&self, limit: u32, convert: &str) -> Result<Vec<CryptoData>> {
+        Ok(api::fetch_listings(&self.0.api_key, limit, convert).await?)
+    }
+}
+
+#[async_trait]
+impl PriceProvider for CoinGecko {
+    async fn fetch_prices(&self, token_names: &[String], convert: &str) -> Result<PriceFetch> {
+        let s = &self.0;
+        Ok(coingecko::fetch_prices(&s.api_key, token_names, convert, s.batch_size, s.max_retries, s.retry_base_ms).await?)
+    }
+
+    async fn fetch_fear_greed(&self, limit: &str) -> Result<Vec<FearGreedData>> {
+        Ok(coingecko::fetch_fear_greed(limit).await?)
+    }
+
+    async fn fetch_global_metrics(&self, convert: &str) -> Result<GlobalMetrics> {
+        Ok(coingecko::fetch_global_metrics(&self.0.api_key, convert).await?)
+    }
+
+    async fn fetch_ohlcv(&self, coin: &CryptoData, count: u32, convert: &str) -> Result<Vec<OhlcvQuote>> {
+        // CoinGecko ids are kept as the slug
+        let coin_id = coin.slug.as_deref()
+            .ok_or_else(|| anyhow::anyhow!("No CoinGecko id for {}", coin.symbol))?;
+        Ok(coingecko::fetch_ohlcv(&self.0.api_key, coin_id, count, convert).await?)
+    }
+
+    async fn fetch_listings(&self, limit: u32, convert: &str) -> Result<Vec<CryptoData>> {
+        let s = &self.0;
+        Ok(coingecko::fetch_listings(&s.api_key, limit, convert, s.max_retries, s.retry_base_ms).await?)
+    }
+}
+
+#[async_trait]
+impl PriceProvider for Demo {
+    async fn fetch_prices(&self, token_names: &[String], convert: &str) -> Result<PriceFetch> {
+        let data = demo::prices(token_names, convert);
+        Ok(PriceFetch {
+            missing: api::missing_tokens(token_names, &data),
+            data,
+            warnings: Vec::new(),
+        })
+    }
+
+    async fn fetch_fear_greed(&self, limit: &str) -> Result<Vec<FearGreedData>> {
+        Ok(demo::fear_greed(limit))
+    }
+
+    async fn fetch_global_metrics(&self, convert: &str) -> Result<GlobalMetrics> {
+        Ok(demo::global_metrics(convert))
+    }
+
+    async fn fetch_ohlcv(&self, coin: &CryptoData, count: u32, convert: &str) -> Result<Vec<OhlcvQuote>> {
+        Ok(demo::ohlcv(coin.id, count, convert))
+    }
+
+    async fn fetch_listings(&self, limit: u32, convert: &str) -> Result<Vec<CryptoData>> {
+        Ok(demo::listings(limit, convert))
+    }
+}
@@ -0,0 +1,88 @@
+use std::sync::Arc;
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use tokio::sync::watch;
+
+use crate::app::snapshot;
+use crate::app::state::App;
+use crate::services::logger::{log_error, log_info};
+
+/// The JSON bodies served, rendered by the TUI loop whenever its state changes. The
+/// server only ever reads these, so a request never waits on a fetch or command the
+/// TUI is busy with
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    portfolio: Option<Arc<str>>,
+    prices: Option<Arc<str>>,
+}
+
+impl Snapshot {
+    /// Renders both endpoints from the current state. A body that fails to render is
+    /// logged and served as an error until the next update
+    pub fn of(app: &App) -> Self {
+        let render = |body: anyhow::Result<String>| match body {
+            Ok(body) => Some(Arc::from(body)),
+            Err(e) => {
+                log_error("Server", &format!("Failed to render snapshot: {}", e)).unwrap_or(());
+                None
+            }
+        };
+        Snapshot {
+            portfolio: render(snapshot::render_portfolio_json(app)),
+            prices: render(snapshot::render_prices_json(app)),
+        }
+    }
+}
+
+/// Re-renders the served snapshot, waking no one if nothing changed
+pub fn publish(snapshots: &watch::Sender<Snapshot>, app: &App) {
+    let snapshot = Snapshot::of(app);
+    snapshots.send_if_modified(|current| {
+        if *current == snapshot {
+            return false;
+        }
+        *current = snapshot;
+        true
+    });
+}
+
+/// Serves the portfolio at `/portfolio` and the watchlist at `/prices` as JSON on
+/// localhost, for scraping into dashboards. Failing to bind is logged and ends the task
+pub async fn serve(port: u16, snapshots: watch::Receiver<Snapshot>) {
+    let router = Router::new()
+        .route("/portfolio", get(portfolio))
+        .route("/prices", get(prices))
+        .with_state(snapshots);
+
+    let listener = match tokio::net::TcpListener::bind(("127.0.0.1", port)).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            log_error("Server", &format!("Failed to listen on port {}: {}", port, e)).unwrap_or(());
+            return;
+        }
+    };
+    log_info("Server", &format!("Serving portfolio data on http://127.0.0.1:{}", port)).unwrap_or(());
+    if let Err(e) = axum::serve(listener, router).await {
+        log_error("Server", &e.to_string()).unwrap_or(());
+    }
+}
+
+async fn portfolio(State(snapshots): State<watch::Receiver<Snapshot>>) -> Response {
+    json_response(snapshots.borrow().portfolio.clone())
+}
+
+async fn prices(State(snapshots): State<watch::Receiver<Snapshot>>) -> Response {
+    json_response(snapshots.borrow().prices.clone())
+}
+
+fn json_response(body: Option<Arc<str>>) -> Response {
+    match body {
+        Some(body) => ([(header::CONTENT_TYPE, "application/json")], body.to_string()).into_response(),
+        None => (StatusCode::INTERNAL_SERVER_ERROR, "Failed to render snapshot").into_response(),
+    }
+}
@@ -1,35 +1,328 @@
-/// Formats a volume value into a human-readable string with appropriate unit (B/M)
-/// Returns "N/A" if the volume is None
-pub fn format_volume(volume: Option<f64>) -> String {
-    volume.map_or("N/A".to_string(), |v| {
-        if v >= 1_000_000_000.0 {
-            format!("${:.1}B", v / 1_000_000_000.0)
-        } else {
-            format!("${:.1}M", v / 1_000_000.0)
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Decimal and digit grouping conventions, selected with the `number_locale` config option
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumberLocale {
+    /// 1,234.56
+    En,
+    /// 1.234,56
+    De,
+    /// 1 234,56
+    Fr,
+    /// 1'234.56
+    Ch,
+}
+
+impl NumberLocale {
+    /// Resolves a language code such as "en", "de" or "fr-CA", falling back to English
+    /// for missing or unknown codes
+    pub fn from_name(name: Option<&str>) -> Self {
+        let name = name.unwrap_or_default().to_lowercase();
+        if name == "ch" || name.ends_with("-ch") {
+            return NumberLocale::Ch;
+        }
+        match name.split(['-', '_']).next().unwrap_or_default() {
+            "de" | "es" | "it" | "nl" | "pt" | "da" | "id" | "tr" => NumberLocale::De,
+            "fr" | "sv" | "nb" | "no" | "fi" | "pl" | "cs" | "ru" | "uk" => NumberLocale::Fr,
+            _ => NumberLocale::En,
         }
-    })
+    }
+
+    /// The (grouping, decimal) separators
+    fn separators(self) -> (char, char) {
+        match self {
+            NumberLocale::En => (',', '.'),
+            NumberLocale::De => ('.', ','),
+            NumberLocale::Fr => (' ', ','),
+            NumberLocale::Ch => ('\'', '.'),
+        }
+    }
 }
 
-/// Formats a market cap value into a human-readable string with appropriate unit (B/M)
+static NUMBER_LOCALE: AtomicU8 = AtomicU8::new(NumberLocale::En as u8);
+
+/// Sets the locale every formatter in this module uses
+pub fn set_number_locale(locale: NumberLocale) {
+    NUMBER_LOCALE.store(locale as u8, Ordering::Relaxed);
+}
+
+fn number_locale() -> NumberLocale {
+    match NUMBER_LOCALE.load(Ordering::Relaxed) {
+        x if x == NumberLocale::De as u8 => NumberLocale::De,
+        x if x == NumberLocale::Fr as u8 => NumberLocale::Fr,
+        x if x == NumberLocale::Ch as u8 => NumberLocale::Ch,
+        _ => NumberLocale::En,
+    }
+}
+
+/// Swaps the decimal point of an already formatted number for the locale's separator
+fn localize(formatted: String) -> String {
+    match number_locale().separators().1 {
+        '.' => formatted,
+        decimal => formatted.replace('.', &decimal.to_string()),
+    }
+}
+
+/// Formats a number with the given decimal places and the locale's decimal separator,
+/// without grouping (for percentages and other small figures)
+pub fn format_decimal(value: f64, decimals: usize) -> String {
+    localize(format!("{:.*}", decimals, value))
+}
+
+/// Returns the display symbol for a fiat currency code (e.g. "EUR" -> "€")
+/// Unknown codes fall back to the code itself followed by a space
+pub fn currency_symbol(currency: &str) -> String {
+    match currency.to_uppercase().as_str() {
+        "USD" | "AUD" | "CAD" | "NZD" | "SGD" | "HKD" | "MXN" => "$".to_string(),
+        "EUR" => "€".to_string(),
+        "GBP" => "£".to_string(),
+        "JPY" | "CNY" => "¥".to_string(),
+        "KRW" => "₩".to_string(),
+        "INR" => "₹".to_string(),
+        "RUB" => "₽".to_string(),
+        "TRY" => "₺".to_string(),
+        "BRL" => "R$".to_string(),
+        "CHF" => "CHF ".to_string(),
+        other => format!("{} ", other),
+    }
+}
+
+/// Formats a large amount with a unit suffix (T/B/M/K) and one decimal place.
+/// The sign is kept in front of the currency symbol so negative values read naturally
+fn format_with_unit(value: f64, symbol: &str) -> String {
+    const UNITS: [&str; 5] = ["", "K", "M", "B", "T"];
+    let sign = if value < 0.0 { "-" } else { "" };
+    let mut scaled = value.abs();
+    let mut unit = 0;
+    // Step up on the rounded value, so 999,999 reads "1.0M" rather than "1000.0K"
+    while unit < UNITS.len() - 1 && (scaled * 10.0).round() / 10.0 >= 1_000.0 {
+        scaled /= 1_000.0;
+        unit += 1;
+    }
+    format!("{}{}{}{}", sign, symbol, format_decimal(scaled, 1), UNITS[unit])
+}
+
+/// Formats a volume value into a human-readable string with appropriate unit (T/B/M/K)
+/// Returns "N/A" if the volume is None
+pub fn format_volume(volume: Option<f64>, currency: &str) -> String {
+    volume.map_or("N/A".to_string(), |v| format_with_unit(v, &currency_symbol(currency)))
+}
+
+/// Formats a market cap value into a human-readable string with appropriate unit (T/B/M/K)
 /// Returns "N/A" if the market cap is None
-pub fn format_market_cap(market_cap: Option<f64>) -> String {
-    market_cap.map_or("N/A".to_string(), |v| {
-        if v >= 1_000_000_000.0 {
-            format!("${:.1}B", v / 1_000_000_000.0)
-        } else {
-            format!("${:.1}M", v / 1_000_000.0)
+pub fn format_market_cap(market_cap: Option<f64>, currency: &str) -> String {
+    market_cap.map_or("N/A".to_string(), |v| format_with_unit(v, &currency_symbol(currency)))
+}
+
+/// Formats a number with the given decimal places and thousands separators in the
+/// integer part, e.g. 1234567.891 with 2 decimals -> "1,234,567.89" ("1.234.567,89"
+/// with the German locale)
+pub fn group_thousands(value: f64, decimals: usize) -> String {
+    let (group, decimal) = number_locale().separators();
+    let formatted = format!("{:.*}", decimals, value.abs());
+    let (integer, fraction) = match formatted.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (formatted.as_str(), None),
+    };
+
+    let mut grouped = String::with_capacity(formatted.len() + integer.len() / 3);
+    for (i, digit) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            grouped.push(group);
         }
-    })
+        grouped.push(digit);
+    }
+    if let Some(fraction) = fraction {
+        grouped.push(decimal);
+        grouped.push_str(fraction);
+    }
+
+    // Values that round to zero shouldn't render as "-0.00"
+    if value < 0.0 && grouped.chars().any(|c| c.is_ascii_digit() && c != '0') {
+        format!("-{}", grouped)
+    } else {
+        grouped
+    }
 }
 
-/// Formats a price value with appropriate decimal places based on its magnitude
+/// Prefixes a grouped number with the currency symbol, keeping the sign in front
+fn with_symbol(grouped: String, symbol: &str) -> String {
+    match grouped.strip_prefix('-') {
+        Some(abs) => format!("-{}{}", symbol, abs),
+        None => format!("{}{}", symbol, grouped),
+    }
+}
+
+/// Formats a price value with appropriate decimal places based on its magnitude,
+/// prefixed with the symbol of the given currency and grouped by thousands
 /// - For prices >= 1000: 2 decimal places
 /// - For prices >= 1: 3 decimal places
-/// - For prices < 1: 6 decimal places
-pub fn format_price(price: f64) -> String {
-    match price {
-        p if p >= 1000.0 => format!("${:.2}", p),
-        p if p >= 1.0 => format!("${:.3}", p),
-        p => format!("${:.6}", p)
-    }
-}
\ No newline at end of file
+/// - For prices >= 0.0001: 6 decimal places
+/// - For smaller prices: 4 significant figures, so micro-cap coins stay distinguishable
+pub fn format_price(price: f64, currency: &str) -> String {
+    let decimals = match price.abs() {
+        p if p >= 1000.0 => 2,
+        p if p >= 1.0 => 3,
+        p if p >= 0.0001 || p == 0.0 => 6,
+        p => ((-p.log10()).floor() as usize + 4).min(16),
+    };
+    with_symbol(group_thousands(price, decimals), &currency_symbol(currency))
+}
+
+/// Formats a monetary total (portfolio value, P/L, ...) with 2 decimal places,
+/// thousands separators and the currency symbol
+pub fn format_amount(amount: f64, currency: &str) -> String {
+    with_symbol(group_thousands(amount, 2), &currency_symbol(currency))
+}
+
+/// Formats a token amount with at least `precision` decimal places. Amounts below 1
+/// get extra decimals so that `precision` significant digits remain visible
+/// (0.00012345 with precision 4 shows as 0.0001235 rather than 0.0001)
+pub fn format_holdings(amount: f64, precision: usize) -> String {
+    let abs = amount.abs();
+    let decimals = if abs > 0.0 && abs < 1.0 {
+        let leading_zeros = (-abs.log10()).floor() as usize;
+        (leading_zeros + precision).min(12)
+    } else {
+        precision
+    };
+    format_decimal(amount, decimals)
+}
+
+/// Formats a percent change with an explicit sign, or "N/A" when it is unknown
+pub fn format_percent(value: Option<f64>, precision: usize) -> String {
+    value.map_or("N/A".to_string(), |v| localize(format!("{:+.*}%", precision, v)))
+}
+
+/// Renders `values` as a row of block characters, one per value, scaled so the
+/// smallest maps to the lowest block and the largest to the full one. A flat
+/// series is drawn at mid height; non-finite values are skipped
+pub fn sparkline(values: &[f64]) -> String {
+    const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let values: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let range = max - min;
+
+    values.iter()
+        .map(|v| {
+            if range <= 0.0 {
+                return BLOCKS[BLOCKS.len() / 2 - 1];
+            }
+            let level = ((v - min) / range * (BLOCKS.len() - 1) as f64).round() as usize;
+            BLOCKS[level.min(BLOCKS.len() - 1)]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// The locale is global, so tests that format numbers take turns setting it
+    static LOCALE_LOCK: Mutex<()> = Mutex::new(());
+
+    fn with_locale<T>(locale: NumberLocale, f: impl FnOnce() -> T) -> T {
+        let _guard = LOCALE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        set_number_locale(locale);
+        let result = f();
+        set_number_locale(NumberLocale::En);
+        result
+    }
+
+    #[test]
+    fn format_with_unit_picks_unit_after_rounding() {
+        with_locale(NumberLocale::En, || {
+            assert_eq!(format_with_unit(999.0, "$"), "$999.0");
+            assert_eq!(format_with_unit(999_949.0, "$"), "$999.9K");
+            assert_eq!(format_with_unit(999_999.0, "$"), "$1.0M");
+            assert_eq!(format_with_unit(1_000_000.0, "$"), "$1.0M");
+            assert_eq!(format_with_unit(999_999_999.0, "$"), "$1.0B");
+            assert_eq!(format_with_unit(1e12, "$"), "$1.0T");
+            assert_eq!(format_with_unit(1e15, "$"), "$1000.0T");
+        });
+    }
+
+    #[test]
+    fn format_with_unit_keeps_sign_before_symbol() {
+        with_locale(NumberLocale::En, || {
+            assert_eq!(format_with_unit(-1_500.0, "$"), "-$1.5K");
+            assert_eq!(format_with_unit(-999_999.0, "$"), "-$1.0M");
+            assert_eq!(format_with_unit(-2.5e9, "€"), "-€2.5B");
+        });
+    }
+
+    #[test]
+    fn group_thousands_groups_integer_part() {
+        with_locale(NumberLocale::En, || {
+            assert_eq!(group_thousands(0.0, 2), "0.00");
+            assert_eq!(group_thousands(999.0, 2), "999.00");
+            assert_eq!(group_thousands(1_000.0, 2), "1,000.00");
+            assert_eq!(group_thousands(65_432.1, 2), "65,432.10");
+            assert_eq!(group_thousands(1_234_567.891, 2), "1,234,567.89");
+            assert_eq!(group_thousands(1_234_567.0, 0), "1,234,567");
+        });
+    }
+
+    #[test]
+    fn group_thousands_keeps_small_decimals() {
+        with_locale(NumberLocale::En, || {
+            assert_eq!(group_thousands(0.5, 3), "0.500");
+            assert_eq!(group_thousands(0.000123, 6), "0.000123");
+        });
+    }
+
+    #[test]
+    fn group_thousands_signs() {
+        with_locale(NumberLocale::En, || {
+            assert_eq!(group_thousands(-1_234.5, 2), "-1,234.50");
+            assert_eq!(group_thousands(-0.5, 2), "-0.50");
+            // Rounds to zero, so no sign
+            assert_eq!(group_thousands(-0.001, 2), "0.00");
+            assert_eq!(group_thousands(-0.0, 2), "0.00");
+        });
+    }
+
+    #[test]
+    fn group_thousands_follows_locale() {
+        let value = -1_234_567.891;
+        assert_eq!(with_locale(NumberLocale::En, || group_thousands(value, 2)), "-1,234,567.89");
+        assert_eq!(with_locale(NumberLocale::De, || group_thousands(value, 2)), "-1.234.567,89");
+        assert_eq!(with_locale(NumberLocale::Fr, || group_thousands(value, 2)), "-1 234 567,89");
+        assert_eq!(with_locale(NumberLocale::Ch, || group_thousands(value, 2)), "-1'234'567.89");
+        assert_eq!(with_locale(NumberLocale::De, || group_thousands(999.5, 1)), "999,5");
+    }
+
+    #[test]
+    fn sparkline_spans_block_range() {
+        let line: Vec<char> = sparkline(&[3.0, 1.0, 2.0, 5.0]).chars().collect();
+        assert_eq!(line.len(), 4);
+        assert_eq!(line[1], '▁');
+        assert_eq!(line[3], '█');
+        assert!(line[1] < line[2] && line[2] < line[0] && line[0] < line[3]);
+    }
+
+    #[test]
+    fn sparkline_flat_and_empty() {
+        let flat = sparkline(&[7.0, 7.0, 7.0]);
+        assert_eq!(flat.chars().count(), 3);
+        assert!(flat.chars().all(|c| c == flat.chars().next().unwrap()));
+        assert_eq!(sparkline(&[]), "");
+    }
+
+    #[test]
+    fn sparkline_skips_non_finite_values() {
+        let blocks = '▁'..='█';
+        for values in [
+            vec![1.0, f64::NAN, 2.0],
+            vec![f64::INFINITY, 1.0, f64::NEG_INFINITY, 3.0],
+            vec![f64::NAN, f64::INFINITY],
+        ] {
+            let line = sparkline(&values);
+            assert!(line.chars().all(|c| blocks.contains(&c)), "{:?} escaped the block range: {}", values, line);
+        }
+        assert_eq!(sparkline(&[1.0, f64::NAN, 2.0]), "▁█");
+        assert_eq!(sparkline(&[f64::NAN, f64::INFINITY]), "");
+    }
+}
@@ -0,0 +1,26 @@
+/// Look-back of the watchlist's RSI column, in daily closes
+pub const RSI_PERIOD: usize = 14;
+
+/// Relative strength index of `closes` (oldest first) over `period` price changes, using
+/// Wilder's smoothing for every change after the first `period`. `None` until there are
+/// more than `period` closes
+pub fn rsi(closes: &[f64], period: usize) -> Option<f64> {
+    if period == 0 || closes.len() <= period {
+        return None;
+    }
+
+    let changes: Vec<f64> = closes.windows(2).map(|pair| pair[1] - pair[0]).collect();
+    let (seed, rest) = changes.split_at(period);
+    let mut avg_gain = seed.iter().map(|change| change.max(0.0)).sum::<f64>() / period as f64;
+    let mut avg_loss = seed.iter().map(|change| (-change).max(0.0)).sum::<f64>() / period as f64;
+    for change in rest {
+        avg_gain = (avg_gain * (period - 1) as f64 + change.max(0.0)) / period as f64;
+        avg_loss = (avg_loss * (period - 1) as f64 + (-change).max(0.0)) / period as f64;
+    }
+
+    // A flat series has no strength either way
+    if avg_loss == 0.0 {
+        return Some(if avg_gain == 0.0 { 50.0 } else { 100.0 });
+    }
+    Some(100.0 - 100.0 / (1.0 + avg_gain / avg_loss))
+}
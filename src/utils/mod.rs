@@ -1 +1,2 @@
 pub mod formatters;
+pub mod indicators;